@@ -1,10 +1,12 @@
 use cars::*;
+use decode::*;
 use definitions::*;
+use ggez::event::{Keycode, Mod};
 use ggez::graphics::*;
 use ggez::*;
 use graphs::nets::*;
 use graphs::*;
-use std;
+use source::*;
 use std::f32;
 use std::time::Instant;
 use util::*;
@@ -14,8 +16,53 @@ const _MAGIC_SPEED: f32 = 0.004787775304098672;
 
 pub const FONT_OFFSET: f32 = 2.0;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LeftPanel {
+    Gears,
+    Dampers,
+    Roll,
+    Diff,
+    Slip,
+    Inputs,
+    Friction,
+    Grip,
+    Aero,
+    Balance,
+    Strategy,
+    Engine,
+    Damage,
+    Standings,
+    Multiclass,
+    Rival,
+    Details,
+}
+
+impl LeftPanel {
+    pub fn next(&self) -> LeftPanel {
+        match *self {
+            LeftPanel::Gears => LeftPanel::Dampers,
+            LeftPanel::Dampers => LeftPanel::Roll,
+            LeftPanel::Roll => LeftPanel::Diff,
+            LeftPanel::Diff => LeftPanel::Slip,
+            LeftPanel::Slip => LeftPanel::Inputs,
+            LeftPanel::Inputs => LeftPanel::Friction,
+            LeftPanel::Friction => LeftPanel::Grip,
+            LeftPanel::Grip => LeftPanel::Aero,
+            LeftPanel::Aero => LeftPanel::Balance,
+            LeftPanel::Balance => LeftPanel::Strategy,
+            LeftPanel::Strategy => LeftPanel::Engine,
+            LeftPanel::Engine => LeftPanel::Damage,
+            LeftPanel::Damage => LeftPanel::Standings,
+            LeftPanel::Standings => LeftPanel::Multiclass,
+            LeftPanel::Multiclass => LeftPanel::Rival,
+            LeftPanel::Rival => LeftPanel::Details,
+            LeftPanel::Details => LeftPanel::Gears,
+        }
+    }
+}
+
 pub struct PC2App {
-    shared_data: *const SharedMemory,
+    source: Box<dyn TelemetrySource>,
     local_copy: SharedMemory,
     start_time: Instant,
     rpm_step: i32,
@@ -24,9 +71,28 @@ pub struct PC2App {
     max_rpm: i32,
     current_car: String,
     current_track: String,
+    current_class: String,
     power_data: PowerGraphData,
     stupid_graphs: StupidGraphData,
     rake_graph: RakeGraphData,
+    damper_graphs: DamperGraphData,
+    roll_graph: RollGraphData,
+    diff_graph: DiffGraphData,
+    slip_graph: SlipGraphData,
+    inputs_graph: InputsGraphData,
+    friction_circle: FrictionCircleData,
+    understeer: UndersteerEstimator,
+    aero_graph: AeroGraphData,
+    balance_graph: BalanceGraphData,
+    strategy: StrategyData,
+    engine_health: EngineHealthData,
+    damage: DamageMonitor,
+    standings: StandingsData,
+    multiclass: MulticlassData,
+    rival: RivalData,
+    anomalies: Vec<Anomaly>,
+    left_panel: LeftPanel,
+    labels: TextCache,
     optimized_text: OptimizedText,
     numeric_text_cache: NumericTextCache,
     nets_and_borders: NetsAndBorders,
@@ -38,7 +104,7 @@ pub struct PC2App {
 impl PC2App {
     pub fn new(
         ctx: &mut Context,
-        sm: *const SharedMemory,
+        source: Box<dyn TelemetrySource>,
         screen_width: f32,
         screen_height: f32,
         rpm_step: i32,
@@ -60,14 +126,15 @@ impl PC2App {
             NetsAndBorders::new(ctx, &Point2::new(screen_width, screen_height), &small_font);
 
         let cars_info = AllCarsData::new(large_font);
+        let labels = TextCache::new(small_font);
 
         graphics::set_background_color(ctx, Color::from_rgb(18, 31, 52));
 
         // "MAXHP: {} MAXRPM: {}, GEAR: {}, RPM: {}, HP: {}",
         PC2App {
-            shared_data: sm,
+            source,
             start_time: Instant::now(),
-            local_copy: unsafe { std::ptr::read_volatile(sm) },
+            local_copy: SharedMemory::zeroed(),
             optimized_text,
             current_gear: 0,
             current_rpm: 0,
@@ -75,8 +142,27 @@ impl PC2App {
             power_data: PowerGraphData::new(rpm_step),
             stupid_graphs: StupidGraphData::new(1000f32),
             rake_graph: RakeGraphData::new(),
+            damper_graphs: DamperGraphData::new(),
+            roll_graph: RollGraphData::new(),
+            diff_graph: DiffGraphData::new(),
+            slip_graph: SlipGraphData::new(),
+            inputs_graph: InputsGraphData::new(),
+            friction_circle: FrictionCircleData::new(),
+            understeer: UndersteerEstimator::new(),
+            aero_graph: AeroGraphData::new(),
+            balance_graph: BalanceGraphData::new(),
+            strategy: StrategyData::new(),
+            engine_health: EngineHealthData::new(),
+            damage: DamageMonitor::new(rpm_step),
+            standings: StandingsData::new(1000f32),
+            multiclass: MulticlassData::new(),
+            rival: RivalData::new(),
+            anomalies: Vec::new(),
+            left_panel: LeftPanel::Gears,
+            labels,
             current_car: String::new(),
             current_track: String::new(),
+            current_class: String::new(),
             screen_width,
             screen_height,
             numeric_text_cache,
@@ -97,43 +183,65 @@ impl PC2App {
 
 impl event::EventHandler for PC2App {
     fn update(&mut self, _ctx: &mut Context) -> GameResult<()> {
-        let update_index = unsafe { std::ptr::read_volatile(&(*self.shared_data).mSequenceNumber) };
-
-        if update_index % 2 != 0 || update_index == self.local_copy.mSequenceNumber {
-            return Ok(());
-        }
+        let local_copy = match self.source.poll() {
+            Some(local_copy) => local_copy,
+            None => return Ok(()),
+        };
 
-        let local_copy = unsafe { std::ptr::read_volatile(self.shared_data) };
-
-        if local_copy.mSequenceNumber != update_index {
-            return Ok(());
-        }
+        self.anomalies = local_copy.anomalies();
 
         let track_name = local_copy.mTrackLocation.clone().to_string();
         let car_name = local_copy.mCarName.clone().to_string();
+        let class_name = local_copy.mCarClassName.clone().to_string();
 
         if track_name.is_empty() || car_name.is_empty() {
             return Ok(());
         }
 
-        if self.current_car != car_name || self.current_track != track_name {
+        let car_changed = self.current_car != car_name || self.current_track != track_name;
+        if car_changed {
             self.current_car = car_name.clone();
             self.current_track = track_name.clone();
             self.max_rpm = local_copy.mMaxRPM as i32;
             self.power_data = PowerGraphData::new(self.rpm_step);
             self.stupid_graphs = StupidGraphData::new(local_copy.mTrackLength);
             self.rake_graph = RakeGraphData::new();
+            self.damper_graphs = DamperGraphData::new();
+            self.roll_graph = RollGraphData::new();
+            self.diff_graph = DiffGraphData::new();
+            self.slip_graph = SlipGraphData::new();
+            self.inputs_graph = InputsGraphData::new();
+            self.friction_circle = FrictionCircleData::new();
+            self.understeer = UndersteerEstimator::new();
+            self.aero_graph = AeroGraphData::new();
+            self.balance_graph = BalanceGraphData::new();
+            self.strategy = StrategyData::new();
+            self.damage = DamageMonitor::new(self.rpm_step);
+            self.standings = StandingsData::new(local_copy.mTrackLength);
+            self.rival = RivalData::new();
+        }
 
-            self.cars_info.set(&car_name);
+        //class may come later than car, it still helps to pick the car
+        if car_changed || self.current_class != class_name {
+            self.current_class = class_name;
 
-            let car_name = self.cars_info.good_name().unwrap_or(car_name);
+            let car_display_name = local_copy.car_display_name();
+            self.cars_info
+                .set(&car_display_name, &local_copy.car_class_display_name());
 
-            let mut title = car_name;
+            let mut title = self.cars_info.good_name().unwrap_or(car_display_name);
             title.push_str(" @ ");
-            title.push_str(&track_name);
+            title.push_str(&local_copy.track_display_name());
+            title.push_str(" (");
+            title.push_str(&self.source.describe());
+            title.push_str(")");
             graphics::get_window_mut(_ctx).set_title(&title).unwrap();
         }
 
+        self.standings
+            .update(&local_copy, self.start_time.elapsed());
+        self.rival.update(&local_copy);
+
         let inputs = Inputs::from(&local_copy);
         let current_rpm_f32 = local_copy.mRpm;
         let current_rpm = current_rpm_f32 as i32;
@@ -150,17 +258,104 @@ impl event::EventHandler for PC2App {
             .add(rpm, inputs.throttle, currents_only);
         self.power_data.torque.add(rpm, torque, currents_only);
         self.power_data.power.add(rpm, power, currents_only);
+        self.damage.add_power(rpm, power, currents_only);
 
         //stupid stuff
         if self.current_gear != local_copy.mGear {
             self.current_gear = local_copy.mGear;
         } else {
-            if local_copy.mGameState == GameState::GAME_INGAME_PLAYING {
+            let playing = local_copy.game_state() == Checked::Known(GameState::GAME_INGAME_PLAYING);
+            if let (true, Some(ix)) = (playing, local_copy.viewed_participant()) {
                 let front_rh = local_copy.mSuspensionTravel.front_avg();
                 let rear_rh = local_copy.mSuspensionTravel.rear_avg();
 
                 self.rake_graph
                     .add(front_rh, rear_rh, self.start_time.elapsed());
+
+                let lap = local_copy.mParticipantInfo.data[ix].mCurrentLap;
+                let distance = local_copy.mParticipantInfo.data[ix].mCurrentLapDistance;
+
+                self.damper_graphs.add(
+                    &local_copy.mSuspensionTravel,
+                    &local_copy.mSuspensionVelocity,
+                    lap,
+                );
+
+                self.inputs_graph.add(
+                    lap,
+                    distance,
+                    &inputs,
+                    local_copy.mLastLapTime,
+                    local_copy.lap_invalidated(),
+                    self.stupid_graphs.track_length,
+                );
+
+                self.slip_graph.add(
+                    lap,
+                    distance,
+                    local_copy.mSpeed,
+                    &local_copy.mTyreRPS,
+                    &local_copy.mTyreSlipSpeed,
+                    local_copy.anti_lock_active(),
+                    &inputs,
+                );
+
+                let lateral = local_copy.mLocalAcceleration.x / 9.8;
+                let longtitudal = local_copy.mLocalAcceleration.z / 9.8;
+
+                if local_copy.mSpeed > 5f32 {
+                    self.roll_graph
+                        .add(&local_copy.mSuspensionTravel, lateral, longtitudal);
+                }
+
+                let understeer = self.understeer.index(
+                    inputs.steering,
+                    local_copy.mAngularVelocity.y,
+                    local_copy.mLocalVelocity.z.abs(),
+                    lateral,
+                );
+
+                self.balance_graph.add(lap, distance, lateral, understeer);
+
+                let in_pits = local_copy.pit_mode() != Checked::Known(PitMode::PIT_MODE_NONE);
+                self.strategy.add(
+                    lap,
+                    local_copy.mFuelLevel,
+                    local_copy.mFuelCapacity,
+                    local_copy.mLastLapTime,
+                    in_pits,
+                    local_copy.lap_invalidated(),
+                );
+
+                self.engine_health.add(
+                    [
+                        local_copy.mOilTempCelsius,
+                        local_copy.mOilPressureKPa,
+                        local_copy.mWaterTempCelsius,
+                        local_copy.mWaterPressureKPa,
+                        local_copy.mFuelPressureKPa,
+                        local_copy.mEngineDamage,
+                    ],
+                    local_copy.mCarFlags.engine_active(),
+                    local_copy.mCarFlags.engine_warning(),
+                    self.start_time.elapsed(),
+                );
+
+                self.damage.add(&local_copy, self.start_time.elapsed());
+
+                if local_copy.mCrashState == 0 {
+                    self.aero_graph.add(
+                        local_copy.mWings,
+                        local_copy.mSpeed * 3.6,
+                        lateral,
+                        longtitudal,
+                        (
+                            local_copy.mSuspensionTravel.front_avg(),
+                            local_copy.mSuspensionTravel.rear_avg(),
+                        ),
+                        understeer,
+                    );
+                }
             }
 
             if self.current_gear > 0 {
@@ -185,6 +380,12 @@ impl event::EventHandler for PC2App {
                     &inputs,
                 );
 
+                self.diff_graph.add(
+                    &tyre_rps_arr,
+                    local_copy.mLocalAcceleration.x / 9.8,
+                    &inputs,
+                );
+
                 // let factual_speed = local_copy.mSpeed * 3.6;
 
                 // let supposed_wheel_diameter =
@@ -196,8 +397,10 @@ impl event::EventHandler for PC2App {
 
                 let velocity_z = -local_copy.mLocalVelocity.z * 3.6;
 
-                let ix = local_copy.mViewedParticipantIndex as usize;
-                let track_position = local_copy.mParticipantInfo.data[ix].mCurrentLapDistance;
+                let track_position = local_copy
+                    .viewed_participant()
+                    .map(|ix| local_copy.mParticipantInfo.data[ix].mCurrentLapDistance)
+                    .unwrap_or(0f32);
 
                 if velocity_z > 0f32 {
                     self.stupid_graphs.add_ggv(
@@ -205,9 +408,28 @@ impl event::EventHandler for PC2App {
                         track_position,
                         local_copy.mLocalAcceleration.x,
                         local_copy.mLocalAcceleration.z,
+                        local_copy.mSpeed,
+                        local_copy.mWings,
                         &inputs,
                         local_copy.mCrashState,
                     );
+
+                    self.damage.add_grip(
+                        local_copy.mLocalAcceleration.x / 9.8,
+                        local_copy.mLocalAcceleration.z / 9.8,
+                        local_copy.mSpeed * 3.6,
+                        local_copy.mWings,
+                        local_copy.mCrashState,
+                    );
+
+                    if local_copy.mCrashState == 0 {
+                        self.friction_circle.add(
+                            local_copy.mLocalAcceleration.x / 9.8,
+                            local_copy.mLocalAcceleration.z / 9.8,
+                            local_copy.mSpeed,
+                            self.current_gear,
+                        );
+                    }
                 }
             }
         }
@@ -242,6 +464,7 @@ impl event::EventHandler for PC2App {
             &self.numeric_text_cache,
             self.rake_graph.max_height,
             self.rake_graph.min_height,
+            self.left_panel == LeftPanel::Gears,
         )?;
 
         //power
@@ -268,13 +491,129 @@ impl event::EventHandler for PC2App {
                 .unwrap_or(0f32) as i32,
         ];
 
-        self.stupid_graphs.draw(
-            ctx,
-            &self.power_data,
-            &Point2::new(self.screen_width, self.screen_height),
-            self.current_gear,
-            self.max_rpm,
-        )?;
+        match self.left_panel {
+            LeftPanel::Gears => {
+                self.stupid_graphs.draw(
+                    ctx,
+                    &self.power_data,
+                    &screen_size,
+                    self.current_gear,
+                    self.max_rpm,
+                )?;
+                self.slip_graph
+                    .draw_markers(ctx, &screen_size, self.stupid_graphs.track_length)?;
+            }
+            LeftPanel::Dampers => self.damper_graphs.draw(
+                ctx,
+                &screen_size,
+                &mut self.labels,
+                &self.numeric_text_cache,
+            )?,
+            LeftPanel::Roll => self.roll_graph.draw(ctx, &screen_size, &mut self.labels)?,
+            LeftPanel::Diff => self.diff_graph.draw(
+                ctx,
+                &screen_size,
+                &mut self.labels,
+                &self.numeric_text_cache,
+                &self.stupid_graphs.ratios,
+            )?,
+            LeftPanel::Slip => self
+                .slip_graph
+                .draw_list(ctx, &screen_size, &mut self.labels)?,
+            LeftPanel::Inputs => self.inputs_graph.draw(
+                ctx,
+                &screen_size,
+                &mut self.labels,
+                self.stupid_graphs.track_length,
+            )?,
+            LeftPanel::Friction => {
+                self.friction_circle
+                    .draw(ctx, &screen_size, &mut self.labels)?
+            }
+            LeftPanel::Grip => self
+                .stupid_graphs
+                .draw_grip(ctx, &screen_size, &mut self.labels)?,
+            LeftPanel::Aero => {
+                self.aero_graph
+                    .draw(ctx, &screen_size, &mut self.labels, self.local_copy.mWings)?
+            }
+            LeftPanel::Balance => self.balance_graph.draw(
+                ctx,
+                &screen_size,
+                &mut self.labels,
+                self.stupid_graphs.track_length,
+            )?,
+            LeftPanel::Strategy => {
+                let strategy = match self.local_copy.viewed_participant() {
+                    Some(ix) => {
+                        let participant = &self.local_copy.mParticipantInfo.data[ix];
+                        self.strategy.strategy(
+                            self.local_copy.mLapsInEvent,
+                            participant.mLapsCompleted,
+                            self.local_copy.mEventTimeRemaining / 1000f32,
+                            participant.mCurrentLapDistance / self.stupid_graphs.track_length,
+                        )
+                    }
+                    None => None,
+                };
+                self.strategy
+                    .draw(ctx, &screen_size, &mut self.labels, strategy)?
+            }
+            LeftPanel::Engine => self
+                .engine_health
+                .draw(ctx, &screen_size, &mut self.labels)?,
+            LeftPanel::Damage => self.damage.draw(ctx, &screen_size, &mut self.labels)?,
+            LeftPanel::Standings => self.standings.draw(ctx, &screen_size, &mut self.labels)?,
+            LeftPanel::Multiclass => {
+                self.multiclass
+                    .draw(ctx, &screen_size, &mut self.labels, &self.standings)?
+            }
+            LeftPanel::Rival => {
+                self.rival
+                    .draw(ctx, &screen_size, &mut self.labels, &self.standings)?
+            }
+            LeftPanel::Details => draw_car_details(
+                ctx,
+                &screen_size,
+                &mut self.labels,
+                &self.cars_info,
+                &self.power_data,
+            )?,
+        }
+
+        self.engine_health
+            .draw_alert(ctx, &screen_size, &mut self.labels)?;
+        self.multiclass
+            .draw_warning(ctx, &screen_size, &mut self.labels, &self.standings)?;
+
+        if !self.anomalies.is_empty() {
+            let line = self
+                .anomalies
+                .iter()
+                .take(3)
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            graphics::set_color(ctx, Color::from_rgb(250, 0, 0))?;
+            self.labels.draw(
+                ctx,
+                &format!(
+                    "Telemetry anomalies ({}), graphs may be wrong: {}",
+                    self.anomalies.len(),
+                    line
+                ),
+                Point2::new(self.screen_width * 0.01, self.screen_height * 0.06),
+            )?;
+        }
+
+        if let Some(error) = self.cars_info.load_error() {
+            graphics::set_color(ctx, Color::from_rgb(250, 0, 0))?;
+            self.labels.draw(
+                ctx,
+                &format!("Car overrides: {}", error),
+                Point2::new(self.screen_width * 0.01, self.screen_height * 0.09),
+            )?;
+        }
 
         self.optimized_text
             .draw_num_cache(ctx, &values, &self.numeric_text_cache)?;
@@ -289,4 +628,45 @@ impl event::EventHandler for PC2App {
         timer::yield_now();
         Ok(())
     }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        keycode: Keycode,
+        _keymod: Mod,
+        _repeat: bool,
+    ) {
+        match keycode {
+            Keycode::Tab => self.left_panel = self.left_panel.next(),
+            Keycode::Space => match self.left_panel {
+                LeftPanel::Dampers => self.damper_graphs.pin_reference(),
+                LeftPanel::Roll => self.roll_graph.pin_reference(),
+                LeftPanel::Grip => self.stupid_graphs.pin_grip_reference(),
+                LeftPanel::Damage => self.damage.show_power = !self.damage.show_power,
+                LeftPanel::Inputs => {
+                    self.inputs_graph.show_unfiltered = !self.inputs_graph.show_unfiltered
+                }
+                _ => {}
+            },
+            Keycode::Up | Keycode::Down if self.left_panel == LeftPanel::Engine => {
+                self.engine_health.select(keycode == Keycode::Up)
+            }
+            Keycode::Left if self.left_panel == LeftPanel::Engine => {
+                self.engine_health.adjust(-1f32)
+            }
+            Keycode::Right if self.left_panel == LeftPanel::Engine => {
+                self.engine_health.adjust(1f32)
+            }
+            Keycode::Up | Keycode::Down if self.left_panel == LeftPanel::Rival => {
+                self.rival.select(&self.standings, keycode == Keycode::Up)
+            }
+            Keycode::Left if self.left_panel == LeftPanel::Multiclass => {
+                self.multiclass.adjust(-1f32)
+            }
+            Keycode::Right if self.left_panel == LeftPanel::Multiclass => {
+                self.multiclass.adjust(1f32)
+            }
+            _ => {}
+        }
+    }
 }
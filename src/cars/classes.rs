@@ -0,0 +1,232 @@
+use super::*;
+
+/// What a car of the class gets when the car itself is unknown.
+#[derive(Debug, Clone)]
+pub struct ClassDefaults {
+    pub name: String,
+    pub sd_vol: i32,
+    pub sd_tone: i32,
+    pub norm_vol: i32,
+    pub norm_tone: i32,
+    pub power_hp: Option<f32>,
+    pub weight_kg: Option<f32>,
+    /// Database cars of the class.
+    pub cars: Vec<usize>,
+}
+
+/// `[[class_defaults]]` entry, anything left out is averaged over cars of the class.
+#[derive(Deserialize)]
+pub struct ClassOverride {
+    name: String,
+    sd_vol: Option<i32>,
+    sd_tone: Option<i32>,
+    norm_vol: Option<i32>,
+    norm_tone: Option<i32>,
+    power_hp: Option<f32>,
+    weight_kg: Option<f32>,
+}
+
+impl ClassDefaults {
+    fn empty(name: &str, cars: Vec<usize>) -> ClassDefaults {
+        ClassDefaults {
+            name: name.to_string(),
+            sd_vol: 0,
+            sd_tone: 0,
+            norm_vol: 0,
+            norm_tone: 0,
+            power_hp: None,
+            weight_kg: None,
+            cars,
+        }
+    }
+
+    pub fn power_to_weight(&self) -> Option<f32> {
+        hp_per_tonne(self.power_hp, self.weight_kg)
+    }
+}
+
+/// Classes by name, averages of database cars with explicit entries on top,
+/// and errors of entries that were skipped.
+pub fn class_defaults(
+    cars: &[CarInfo],
+    overrides: Vec<ClassOverride>,
+) -> (Vec<ClassDefaults>, Option<String>) {
+    let mut classes: Vec<ClassDefaults> = Vec::new();
+    let mut errors = Vec::new();
+
+    for (ix, car) in cars.iter().enumerate() {
+        let name = match car.class {
+            Some(ref name) => name,
+            None => continue,
+        };
+        match classes.iter().position(|c| same_class(&c.name, name)) {
+            Some(found) => classes[found].cars.push(ix),
+            None => classes.push(ClassDefaults::empty(name, vec![ix])),
+        }
+    }
+
+    for class in classes.iter_mut() {
+        let members: Vec<&CarInfo> = class.cars.iter().map(|ix| &cars[*ix]).collect();
+        let ffb = |value: fn(&CarInfo) -> i32| {
+            average(members.iter().map(|c| value(c) as f32))
+                .map(|v| v.round() as i32)
+                .unwrap_or(0)
+        };
+
+        class.sd_vol = ffb(|c| c.sd_vol);
+        class.sd_tone = ffb(|c| c.sd_tone);
+        class.norm_vol = ffb(|c| c.norm_vol);
+        class.norm_tone = ffb(|c| c.norm_tone);
+        class.power_hp = average(members.iter().filter_map(|c| c.power_hp));
+        class.weight_kg = average(members.iter().filter_map(|c| c.weight_kg));
+    }
+
+    for o in overrides {
+        let found = classes.iter().position(|c| same_class(&c.name, &o.name));
+        let class = match found {
+            Some(ix) => &mut classes[ix],
+            //nothing to average the rest from
+            None if o.sd_vol.is_none()
+                || o.sd_tone.is_none()
+                || o.norm_vol.is_none()
+                || o.norm_tone.is_none() =>
+            {
+                errors.push(format!(
+                    "class {} has no cars, needs sd_vol, sd_tone, norm_vol and norm_tone",
+                    o.name
+                ));
+                continue;
+            }
+            None => {
+                classes.push(ClassDefaults::empty(&o.name, Vec::new()));
+                classes.last_mut().unwrap()
+            }
+        };
+
+        class.sd_vol = o.sd_vol.unwrap_or(class.sd_vol);
+        class.sd_tone = o.sd_tone.unwrap_or(class.sd_tone);
+        class.norm_vol = o.norm_vol.unwrap_or(class.norm_vol);
+        class.norm_tone = o.norm_tone.unwrap_or(class.norm_tone);
+        class.power_hp = o.power_hp.or(class.power_hp);
+        class.weight_kg = o.weight_kg.or(class.weight_kg);
+    }
+
+    classes.sort_by(|a, b| a.name.cmp(&b.name));
+    let error = if errors.is_empty() {
+        None
+    } else {
+        Some(errors.join("; "))
+    };
+    (classes, error)
+}
+
+/// Same name ignoring case and punctuation, game and database may spell it differently.
+pub fn same_class(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+fn average<I: Iterator<Item = f32>>(values: I) -> Option<f32> {
+    let (sum, count) = values.fold((0f32, 0), |(sum, count), v| (sum + v, count + 1));
+    if count > 0 {
+        Some(sum / count as f32)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::data;
+    use super::*;
+
+    #[test]
+    fn bundled_classes_average_their_cars() {
+        let cars = data::bundled();
+        let (classes, error) = class_defaults(&cars, Vec::new());
+        assert!(!classes.is_empty());
+        assert_eq!(error, None);
+
+        for class in classes.iter() {
+            assert!(!class.cars.is_empty(), "{}", class.name);
+            let members: Vec<&CarInfo> = class.cars.iter().map(|ix| &cars[*ix]).collect();
+            for car in members.iter() {
+                assert!(same_class(car.class.as_ref().unwrap(), &class.name));
+            }
+            let lowest = members.iter().map(|c| c.sd_vol).min().unwrap();
+            let highest = members.iter().map(|c| c.sd_vol).max().unwrap();
+            assert!(
+                class.sd_vol >= lowest && class.sd_vol <= highest,
+                "{}",
+                class.name
+            );
+            //game's own made up cars have nothing published
+            if members.iter().any(|c| c.name != "SMS") {
+                assert!(class.power_to_weight().is_some(), "{}", class.name);
+            }
+        }
+
+        let total: usize = classes.iter().map(|c| c.cars.len()).sum();
+        assert_eq!(total, cars.iter().filter(|c| c.class.is_some()).count());
+    }
+
+    #[test]
+    fn overrides_replace_averages_and_add_classes() {
+        let cars = data::bundled();
+        let overrides = vec![
+            ClassOverride {
+                name: "gt3".to_string(),
+                sd_vol: Some(42),
+                sd_tone: None,
+                norm_vol: None,
+                norm_tone: None,
+                power_hp: Some(550f32),
+                weight_kg: Some(1250f32),
+            },
+            ClassOverride {
+                name: "Formula Vee".to_string(),
+                sd_vol: Some(30),
+                sd_tone: Some(40),
+                norm_vol: Some(35),
+                norm_tone: Some(45),
+                power_hp: None,
+                weight_kg: None,
+            },
+        ];
+        let (averages, _) = class_defaults(&cars, Vec::new());
+        let (classes, error) = class_defaults(&cars, overrides);
+        assert_eq!(error, None);
+
+        let gt3 = classes.iter().find(|c| c.name == "GT3").unwrap();
+        let average = averages.iter().find(|c| c.name == "GT3").unwrap();
+        assert_eq!(gt3.sd_vol, 42);
+        assert_eq!(gt3.sd_tone, average.sd_tone);
+        assert_eq!(gt3.power_to_weight(), Some(440f32));
+
+        let vee = classes.iter().find(|c| c.name == "Formula Vee").unwrap();
+        assert!(vee.cars.is_empty());
+        assert_eq!((vee.norm_vol, vee.norm_tone), (35, 45));
+    }
+
+    #[test]
+    fn class_without_cars_needs_all_ffb_values() {
+        let cars = data::bundled();
+        let partial = |name: &str| ClassOverride {
+            name: name.to_string(),
+            sd_vol: Some(30),
+            sd_tone: None,
+            norm_vol: Some(35),
+            norm_tone: None,
+            power_hp: None,
+            weight_kg: None,
+        };
+        let (averages, _) = class_defaults(&cars, Vec::new());
+        let (classes, error) = class_defaults(&cars, vec![partial("Formula Vee"), partial("GT3")]);
+
+        let error = error.unwrap();
+        assert!(error.contains("Formula Vee") && !error.contains("GT3"));
+        assert!(classes.iter().all(|c| c.name != "Formula Vee"));
+        assert_eq!(classes.len(), averages.len());
+        let gt3 = classes.iter().find(|c| c.name == "GT3").unwrap();
+        assert_eq!((gt3.sd_vol, gt3.norm_vol), (30, 35));
+    }
+}
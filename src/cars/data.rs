@@ -1,698 +1,281 @@
-use super::CarInfo;
+use super::*;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use toml;
 
-pub fn create() -> Vec<CarInfo> {
-    vec![
-        CarInfo::new("Acura", "NSX", 50, 45, 55, 50, None),
-        CarInfo::new("Acura", "NSX GT3", 45, 45, 50, 50, None),
-        CarInfo::new(
-            "Agajanian",
-            "Watson Road",
-            40,
-            45,
-            45,
-            50,
-            Some("steer. ratio 14:1, caster 4.0"),
-        ),
-        CarInfo::new("Aston Martin", "DB 11", 55, 50, 60, 55, None),
-        CarInfo::new("Aston Martin", "DBR1 300", 35, 25, 45, 35, None),
-        CarInfo::new("Aston Martin", "Vantage GT12", 50, 45, 55, 50, None),
-        CarInfo::new("Aston Martin", "Vantage GT3", 50, 50, 55, 55, None),
-        CarInfo::new("Aston Martin", "Vantage GT4", 50, 50, 55, 60, None),
-        CarInfo::new("Aston Martin", "Vantage GTE", 50, 50, 55, 60, None),
-        CarInfo::new("Aston Martin", "Vulcan", 40, 55, 45, 60, None),
-        CarInfo::new("Audi", "90 IMSA GTO", 55, 45, 60, 50, None),
-        CarInfo::new("Audi", "A1 Quattro", 60, 40, 70, 45, None),
-        CarInfo::new(
-            "Audi",
-            "R18 etron",
-            45,
-            70,
-            50,
-            75,
-            Some("steer. ratio 14.1"),
-        ),
-        CarInfo::new("Audi", "LMP 900", 50, 35, 50, 40, None),
-        CarInfo::new("Audi", "R8 LMS", 40, 80, 45, 80, Some("(odd)")),
-        CarInfo::new("Audi", "R8 LMS 24h", 40, 80, 45, 80, Some("(odd)")),
-        CarInfo::new("Audi", "R8 V10 plus", 55, 50, 65, 65, None),
-        CarInfo::new("Audi", "V8 DTM", 45, 45, 50, 50, Some("caster 7.5")),
-        CarInfo::new("Audi", "S1 EKS RX", 45, 60, 55, 75, None),
-        CarInfo::new("Audi", "Sport QuattroS1", 65, 30, 70, 45, None),
-        CarInfo::new("BAC", "Mono", 40, 75, 50, 75, Some("steer. ratio 14:1")),
-        CarInfo::new(
-            "Bentley",
-            "Conti  GT3  15",
-            45,
-            70,
-            55,
-            80,
-            Some("steer. ratio 14:1 (odd)"),
-        ),
-        CarInfo::new(
-            "Bentley",
-            "Conti  GT3  16",
-            45,
-            70,
-            55,
-            80,
-            Some("steer. ratio 14:1 (odd)"),
-        ),
-        CarInfo::new(
-            "Bentley",
-            "Conti  GT3 24h",
-            45,
-            70,
-            55,
-            80,
-            Some("steer. ratio 14:1 (odd)"),
-        ),
-        CarInfo::new("Bentley", "Speed 8", 40, 55, 45, 60, Some("caster 9.1")),
-        CarInfo::new("BMW", "1M Coupe", 45, 55, 50, 65, None),
-        CarInfo::new("BMW", "1M StanceWorks", 50, 50, 55, 65, None),
-        CarInfo::new("BMW", "2002StanceWorks", 50, 40, 55, 50, None),
-        CarInfo::new("BMW", "2002 Turbo", 65, 20, 70, 30, None),
-        CarInfo::new("BMW", "320 TC", 65, 40, 70, 45, None),
-        CarInfo::new("BMW", "320 Turbo", 45, 35, 50, 45, None),
-        CarInfo::new("BMW", "M1 Procar", 45, 35, 55, 40, None),
-        CarInfo::new("BMW", "M3 Group A", 65, 35, 70, 45, None),
-        CarInfo::new("BMW", "M3 GT4", 55, 70, 70, 75, None),
-        CarInfo::new("BMW", "M6 GT3", 40, 50, 45, 55, None),
-        CarInfo::new("BMW", "M6 GTLM", 50, 50, 50, 55, None),
-        CarInfo::new("BMW", "V12 LMR", 50, 55, 60, 55, None),
-        CarInfo::new("BMW", "Z4 GT3", 50, 65, 50, 75, Some("caster 11.0")),
-        CarInfo::new(
-            "Cadillac",
-            "ATS VR GT3",
-            40,
-            70,
-            45,
-            80,
-            Some("caster 6.5, (odd)"),
-        ),
-        CarInfo::new("Caterham", "Seven 620R", 45, 50, 50, 55, Some("caster 5.2")),
-        CarInfo::new(
-            "Caterham",
-            "SP 300 R",
-            60,
-            20,
-            70,
-            35,
-            Some("steer. ratio 14:1"),
-        ),
-        CarInfo::new("Chevrolet", "Camaro Z28  69", 30, 35, 40, 45, None),
-        CarInfo::new("Chevrolet", "Camaro ZL1", 80, 30, 85, 35, None),
-        CarInfo::new("Chevrolet", "Corvette C7 R", 55, 40, 60, 50, None),
-        CarInfo::new("Chevrolet", "Corvette Z06", 50, 55, 55, 60, None),
-        CarInfo::new(
-            "Chevrolet",
-            "IR 12 Road",
-            45,
-            50,
-            50,
-            50,
-            Some("steer. ratio 14:1, caster 10.5"),
-        ),
-        CarInfo::new(
-            "Chevrolet",
-            "IR 12 Oval",
-            50,
-            45,
-            55,
-            50,
-            Some("steer. ratio 14:1, caster 10.5"),
-        ),
-        CarInfo::new(
-            "Citroen",
-            "DS3 RX",
-            50,
-            60,
-            55,
-            80,
-            Some("steer. ratio 13:1"),
-        ),
-        CarInfo::new("Ferrari", "288 GTO", 60, 30, 70, 45, None),
-        CarInfo::new("Ferrari", "330 P4", 65, 25, 70, 45, None),
-        CarInfo::new(
-            "Ferrari",
-            "333 SP",
-            45,
-            45,
-            50,
-            50,
-            Some("steer. ratio 14:1, caster 5.5"),
-        ),
-        CarInfo::new("Ferrari", "365 GTB 4", 45, 30, 50, 45, None),
-        CarInfo::new("Ferrari", "488 Chall  APAC", 55, 75, 65, 80, None),
-        CarInfo::new("Ferrari", "488 Chall  EU", 55, 75, 65, 80, None),
-        CarInfo::new("Ferrari", "488 Chall  NA", 55, 75, 65, 80, None),
-        CarInfo::new("Ferrari", "488 GT3", 45, 35, 55, 40, None),
-        CarInfo::new("Ferrari", "488 GTE", 55, 50, 60, 55, None),
-        CarInfo::new("Ferrari", "Enzo", 45, 45, 55, 50, None),
-        CarInfo::new(
-            "Ferrari",
-            "F40 LM",
-            60,
-            40,
-            65,
-            50,
-            Some("steer. ratio 14:1, caster 5.5"),
-        ),
-        CarInfo::new("Ferrari", "F50 GT", 55, 35, 60, 45, None),
-        CarInfo::new("Ferrari", "La Ferrari", 45, 50, 55, 65, None),
-        CarInfo::new("Ford", "Bronco Brocky", 45, 35, 50, 45, None),
-        CarInfo::new("Ford", "Escort 1600", 50, 40, 60, 45, None),
-        CarInfo::new("Ford", "Esc 1600 Racing", 70, 20, 70, 45, None),
-        CarInfo::new("Ford", "Escort 1600 RX", 55, 35, 65, 45, None),
-        CarInfo::new("Ford", "F 150 RTR", 65, 50, 70, 50, None),
-        CarInfo::new(
-            "Ford",
-            "Falcon FG",
-            55,
-            30,
-            55,
-            40,
-            Some("steer. ratio 14:1, caster 6.5"),
-        ),
-        CarInfo::new("Ford", "Focus RS RX", 45, 40, 45, 50, None),
-        CarInfo::new("Ford", "Fusion", 60, 30, 70, 45, None),
-        CarInfo::new("Ford", "GT", 45, 50, 50, 60, None),
-        CarInfo::new("Ford", "GT LM GTE", 45, 45, 50, 50, None),
-        CarInfo::new("Ford", "MK IV", 55, 40, 65, 55, None),
-        CarInfo::new("Ford", "Mustang  66", 55, 40, 60, 45, None),
-        CarInfo::new("Ford", "Mustang  66 RTR", 35, 40, 40, 65, None),
-        CarInfo::new("Ford", "MustangBoss302R", 50, 45, 55, 55, None),
-        CarInfo::new("Ford", "Mustang Cobra", 65, 40, 75, 50, None),
-        CarInfo::new("Ford", "Mustang GT", 70, 30, 80, 35, None),
-        CarInfo::new("Ford", "Mustang RTR GT4", 65, 30, 65, 40, None),
-        CarInfo::new("Ford", "Must RTRSpec 5D", 55, 70, 60, 70, Some("(odd)")),
-        CarInfo::new("Ford", "RS 200 EVO", 70, 30, 70, 45, None),
-        CarInfo::new("Ford", "Sierra Cosworth", 60, 45, 65, 80, None),
-        CarInfo::new("Ford", "Zakspeed Capri", 50, 50, 60, 65, None),
-        CarInfo::new("Ginetta", "G40 GT5", 50, 75, 60, 80, Some("(odd)")),
-        CarInfo::new("Ginetta", "G40 Junior", 40, 70, 55, 80, Some("(odd)")),
-        CarInfo::new("Ginetta", "G55 GT3", 50, 45, 55, 50, None),
-        CarInfo::new("Ginetta", "G55 GT4", 55, 35, 55, 45, None),
-        CarInfo::new("Ginetta", "G57", 50, 50, 55, 55, None),
-        CarInfo::new("Ginetta", "LMP3", 45, 50, 50, 55, None),
-        CarInfo::new(
-            "Honda",
-            "24 Concept",
-            35,
-            55,
-            40,
-            55,
-            Some("steer. ratio 14:1"),
-        ),
-        CarInfo::new("Honda", "Civic Coupe GRC", 40, 35, 45, 45, None),
-        CarInfo::new("Honda", "Civic Type R", 80, 45, 90, 55, None),
-        CarInfo::new(
-            "Honda",
-            "IR 12 Road",
-            45,
-            50,
-            50,
-            50,
-            Some("steer. ratio 14:1, caster 10.5"),
-        ),
-        CarInfo::new(
-            "Honda",
-            "IR 12 Oval",
-            50,
-            45,
-            55,
-            50,
-            Some("steer. ratio 14:1, caster 10.5"),
-        ),
-        CarInfo::new("Jaguar", "E Type Group44", 40, 45, 45, 65, None),
-        CarInfo::new("Jaguar", "F Type SVR", 60, 40, 65, 45, None),
-        CarInfo::new(
-            "Jaguar",
-            "XJ 220 S",
-            35,
-            70,
-            40,
-            85,
-            Some("steer. ratio 14:1, caster 5.0"),
-        ),
-        CarInfo::new(
-            "Jaguar",
-            "XJR 9",
-            45,
-            60,
-            50,
-            65,
-            Some("steer. ratio 14:1, caster 6.0"),
-        ),
-        CarInfo::new(
-            "Jaguar",
-            "XJR 9 LM",
-            45,
-            60,
-            50,
-            65,
-            Some("steer. ratio 14:1, caster 6.0"),
-        ),
-        CarInfo::new("KTM", "X Bow GT4", 55, 30, 65, 45, None),
-        CarInfo::new("KTM", "X Bow R", 45, 75, 55, 85, None),
-        CarInfo::new("Lamborghini", "Aventador", 45, 70, 60, 80, Some("(odd)")),
-        CarInfo::new("Lamborghini", "Diablo", 45, 85, 0, 0, Some("(odd, broken)")),
-        CarInfo::new("Lamborghini", "Huracan", 55, 45, 60, 60, None),
-        CarInfo::new("Lamborghini", "Huracan GT3", 40, 75, 45, 80, Some("(odd)")),
-        CarInfo::new("Lamborghini", "Huracan ST", 60, 70, 70, 80, Some("(odd)")),
-        CarInfo::new(
-            "Lamborghini",
-            "Sesto Elemento",
-            45,
-            60,
-            60,
-            85,
-            Some("(very odd)"),
-        ),
-        CarInfo::new("Lamborghini", "Veneno", 45, 50, 60, 80, Some("(odd)")),
-        CarInfo::new(
-            "Ligier",
-            "JS P2 Honda",
-            35,
-            55,
-            40,
-            60,
-            Some("steer. ratio 14:1, caster 9.4"),
-        ),
-        CarInfo::new(
-            "Ligier",
-            "JS P2 Judd",
-            35,
-            55,
-            40,
-            60,
-            Some("steer. ratio 14:1, caster 9.4"),
-        ),
-        CarInfo::new(
-            "Ligier",
-            "JS P2 Nissan",
-            35,
-            55,
-            40,
-            60,
-            Some("steer. ratio 14:1, caster 9.4"),
-        ),
-        CarInfo::new(
-            "Ligier",
-            "JS P3",
-            45,
-            45,
-            45,
-            50,
-            Some("steer. ratio 14:1. caster 9.2"),
-        ),
-        CarInfo::new("Lotus", "25", 60, 20, 75, 25, Some("steer. ratio 14:1")),
-        CarInfo::new("Lotus", "38", 55, 65, 60, 70, None),
-        CarInfo::new("Lotus", "40", 60, 35, 70, 55, None),
-        CarInfo::new(
-            "Lotus",
-            "49  A ",
-            70,
-            35,
-            80,
-            45,
-            Some("Vintage Tire, steer. ratio 14:1"),
-        ),
-        CarInfo::new(
-            "Lotus",
-            "49  B ",
-            40,
-            45,
-            45,
-            50,
-            Some("default Track Tire"),
-        ),
-        CarInfo::new(
-            "Lotus",
-            "49C  A ",
-            85,
-            25,
-            90,
-            45,
-            Some("Vintage Tire, steer. ratio 14:1"),
-        ),
-        CarInfo::new(
-            "Lotus",
-            "49C  B ",
-            45,
-            45,
-            50,
-            50,
-            Some("default Track Tire"),
-        ),
-        CarInfo::new("Lotus", "51", 60, 25, 65, 35, Some("steer. ratio 14:1")),
-        CarInfo::new("Lotus", "56", 40, 40, 50, 50, None),
-        CarInfo::new("Lotus", "72D", 45, 55, 50, 70, None),
-        CarInfo::new(
-            "Lotus",
-            "78",
-            40,
-            55,
-            45,
-            60,
-            Some("steer. ratio 14:1, caster 3.5"),
-        ),
-        CarInfo::new("Lotus", "98T", 45, 55, 50, 75, Some("steer. ratio 14:1")),
-        CarInfo::new(
-            "Marek",
-            "RP 219D LMP2",
-            40,
-            55,
-            45,
-            60,
-            Some("steer. ratio 14:1, caster 9.4"),
-        ),
-        CarInfo::new(
-            "Marek",
-            "RP 339H LMP1",
-            40,
-            55,
-            45,
-            60,
-            Some("steer. ratio 14:1, caster 10.0"),
-        ),
-        CarInfo::new("Mazda", "MX5 Radbul", 55, 80, 65, 80, None),
-        CarInfo::new("McLaren", "570 S", 35, 40, 40, 50, None),
-        CarInfo::new(
-            "McLaren",
-            "650 S GT3",
-            50,
-            50,
-            50,
-            65,
-            Some("steer. ratio 14:1"),
-        ),
-        CarInfo::new("McLaren", "720 S", 40, 55, 50, 65, None),
-        CarInfo::new("McLaren", "F1", 40, 75, 45, 85, None),
-        CarInfo::new(
-            "McLaren",
-            "F1 GTR LT",
-            50,
-            55,
-            60,
-            70,
-            Some("steer. ratio 14:1"),
-        ),
-        CarInfo::new("McLaren", "P1", 40, 75, 50, 80, Some("(odd)")),
-        CarInfo::new("McLaren", "P1 GTR", 35, 75, 45, 80, Some("(odd)")),
-        CarInfo::new("Mercedes AMG", "A45 4 Matic", 75, 25, 75, 40, None),
-        CarInfo::new("Mercedes AMG", "A45 RX", 55, 35, 55, 50, None),
-        CarInfo::new("Mercedes AMG", "A45 Touring", 60, 40, 65, 45, None),
-        CarInfo::new("Mercedes AMG", "GTR", 45, 55, 50, 65, None),
-        CarInfo::new("Mercedes AMG", "GT3", 40, 70, 45, 80, Some("(odd)")),
-        CarInfo::new("Mercedes AMG", "C63 Coupe", 35, 75, 50, 80, Some("(odd)")),
-        CarInfo::new("Mercedes Benz", "190E DTM", 65, 35, 70, 60, None),
-        CarInfo::new("Mercedes Benz", "300 SEL 6 8", 45, 40, 50, 45, None),
-        CarInfo::new("Mercedes Benz", "300 SL", 35, 50, 50, 50, Some("(odd)")),
-        CarInfo::new("Mercedes Benz", "CLK LM", 55, 45, 55, 50, None),
-        CarInfo::new(
-            "Mercedes Benz",
-            "Sauber C9",
-            50,
-            60,
-            55,
-            65,
-            Some("caster 7.0"),
-        ),
-        CarInfo::new(
-            "Mercedes Benz",
-            "Sauber C9 LM",
-            50,
-            60,
-            55,
-            65,
-            Some("caster 7.0"),
-        ),
-        CarInfo::new(
-            "Mercedes Benz",
-            "SLS AMG GT3",
-            55,
-            75,
-            70,
-            80,
-            Some("(odd)"),
-        ),
-        CarInfo::new("Mini", "Countryman RX", 60, 25, 60, 40, None),
-        CarInfo::new("Mitsubishi", "Lancer EVO IX", 40, 65, 45, 75, None),
-        CarInfo::new("Mitsubishi", "Lancer EVO SVA", 45, 45, 50, 50, None),
-        CarInfo::new("Mitsubishi", "Lancer EVO TME", 45, 55, 50, 60, None),
-        CarInfo::new("Mitsubishi", "Lancer EVO X", 50, 45, 55, 55, None),
-        CarInfo::new("Nissan", "240 ZG GTS", 55, 20, 60, 30, None),
-        CarInfo::new(
-            "Nissan",
-            "280 ZX IMSA",
-            40,
-            50,
-            45,
-            55,
-            Some("steer. ratio 14:1, caster 4.5"),
-        ),
-        CarInfo::new(
-            "Nissan",
-            "300 ZX IMSA",
-            50,
-            45,
-            55,
-            50,
-            Some("steer. ratio 14:1, caster 9.0"),
-        ),
-        CarInfo::new(
-            "Nissan",
-            "300 ZX LM",
-            50,
-            45,
-            55,
-            50,
-            Some("steer. ratio 14:1, caster 9.0"),
-        ),
-        CarInfo::new("Nissan", "GTR GT3", 45, 45, 50, 50, None),
-        CarInfo::new("Nissan", "GTR Nismo", 55, 45, 65, 50, None),
-        CarInfo::new(
-            "Nissan",
-            "GTP ZX T",
-            45,
-            55,
-            50,
-            60,
-            Some("steer. ratio 14:1, caster 7.0"),
-        ),
-        CarInfo::new(
-            "Nissan",
-            "R89C",
-            40,
-            60,
-            45,
-            65,
-            Some("steer. ratio 14:1, caster 6.0"),
-        ),
-        CarInfo::new(
-            "Nissan",
-            "R89C LM",
-            40,
-            60,
-            45,
-            65,
-            Some("steer. ratio 14:1, caster 6.0"),
-        ),
-        CarInfo::new("Nissan", "R390 GT1", 50, 50, 55, 70, None),
-        CarInfo::new("Nissan", "Skyline RS 32", 50, 50, 50, 55, None),
-        CarInfo::new(
-            "Nissan",
-            "Skyl Silhouette",
-            40,
-            50,
-            45,
-            60,
-            Some("caster 4.0"),
-        ),
-        CarInfo::new("Nissan", "Skyline SMS R", 50, 50, 60, 50, None),
-        CarInfo::new("Olsberg", "RX Supercar", 40, 35, 50, 45, None),
-        CarInfo::new("Opel", "Astra", 60, 35, 70, 40, None),
-        CarInfo::new("Oreca", "LMP 2", 40, 55, 45, 60, None),
-        CarInfo::new("Pagani", "Huayra BC", 45, 45, 55, 50, None),
-        CarInfo::new("Pagani", "Zonda Cinque", 45, 60, 50, 75, None),
-        CarInfo::new("Pagani", "Zonda Revo ", 55, 50, 55, 60, None),
-        CarInfo::new("Panoz", "Esperante GT1", 55, 50, 60, 55, None),
-        CarInfo::new("Porsche", "908 03 Spyder", 55, 25, 60, 40, None),
-        CarInfo::new("Porsche", "911 Carrera RSR", 50, 30, 55, 45, None),
-        CarInfo::new("Porsche", "911 GT 98", 55, 40, 60, 50, None),
-        CarInfo::new("Porsche", "911 GT R", 45, 45, 50, 60, None),
-        CarInfo::new("Porsche", "911 GT R 24h", 45, 45, 50, 60, None),
-        CarInfo::new("Porsche", "911 GT3 RS", 45, 45, 50, 60, None),
-        CarInfo::new("Porsche", "911 RSR", 45, 45, 55, 50, None),
-        CarInfo::new("Porsche", "917 10", 45, 25, 55, 50, None),
-        CarInfo::new("Porsche", "917 K", 50, 35, 60, 65, None),
-        CarInfo::new("Porsche", "918 Spyder", 45, 55, 50, 75, None),
-        CarInfo::new("Porsche", "935 77", 50, 50, 55, 60, None),
-        CarInfo::new("Porsche", "935 78", 40, 55, 45, 55, None),
-        CarInfo::new("Porsche", "935 78 81", 40, 55, 45, 55, None),
-        CarInfo::new("Porsche", "935 80", 50, 50, 55, 60, None),
-        CarInfo::new("Porsche", "936 Spyder", 55, 25, 65, 45, Some("caster 4.5")),
-        CarInfo::new(
-            "Porsche",
-            "959 S",
-            25,
-            80,
-            30,
-            80,
-            Some("(odd, tight center)"),
-        ),
-        CarInfo::new(
-            "Porsche",
-            "962C",
-            35,
-            60,
-            40,
-            70,
-            Some("steer. ratio 14:1, caster 9.0"),
-        ),
-        CarInfo::new(
-            "Porsche",
-            "962C LH",
-            35,
-            60,
-            40,
-            70,
-            Some("steer. ratio 14:1, caster 9.0"),
-        ),
-        CarInfo::new("Porsche", "Carrera GT", 40, 55, 45, 65, None),
-        CarInfo::new("Porsche", "Cayman GT4 MR", 50, 45, 55, 55, None),
-        CarInfo::new("Radical", "RXC Turbo", 50, 55, 50, 65, None),
-        CarInfo::new(
-            "Radical",
-            "SR3 RS",
-            65,
-            25,
-            70,
-            50,
-            Some("steer. ratio 14:1, caster 4.5"),
-        ),
-        CarInfo::new(
-            "Radical",
-            "SR8 RX",
-            65,
-            25,
-            70,
-            50,
-            Some("steer. ratio 14:1, caster 4.5"),
-        ),
-        CarInfo::new("Renault", "5 Maxi Turbo", 90, 20, 95, 40, None),
-        CarInfo::new("Renault", "A 442 B", 45, 35, 55, 50, None),
-        CarInfo::new("Renault", "Clio Cup", 65, 40, 65, 55, None),
-        CarInfo::new(
-            "Renault",
-            "Formula Renault",
-            55,
-            30,
-            55,
-            50,
-            Some("steer. ratio 14:1"),
-        ),
-        CarInfo::new("Renault", "Megane RS 275", 45, 50, 50, 55, None),
-        CarInfo::new("Renault", "Megane RX", 55, 35, 60, 40, None),
-        CarInfo::new("Renault", "Megane Touring", 50, 45, 55, 45, None),
-        CarInfo::new("Renault", "Megane V6", 65, 40, 70, 45, None),
-        CarInfo::new("Renault", "RS 01", 50, 55, 55, 65, None),
-        CarInfo::new("Renault", "RS 01 GT3", 60, 70, 70, 75, None),
-        CarInfo::new(
-            "RWD",
-            "P20 LMP2",
-            35,
-            50,
-            40,
-            55,
-            Some("steer. ratio 14:1, caster 9.5"),
-        ),
-        CarInfo::new(
-            "RWD",
-            "P30 LMP1",
-            30,
-            60,
-            35,
-            55,
-            Some("steer. ratio 14:1, caster 10.0"),
-        ),
-        CarInfo::new(
-            "SMS",
-            "Formula A",
-            45,
-            45,
-            50,
-            50,
-            Some("steer. ratio 13:1, caster 7.0"),
-        ),
-        CarInfo::new("SMS", "Formula C", 50, 35, 55, 55, None),
-        CarInfo::new("SMS", "Formula R", 55, 20, 60, 40, None),
-        CarInfo::new(
-            "SMS",
-            "Formula X",
-            50,
-            50,
-            55,
-            55,
-            Some("steer. ratio 13:1"),
-        ),
-        CarInfo::new("SMS", "Kart", 35, 40, 40, 45, Some("steer. ratio 10.4:1")),
-        CarInfo::new("Toyota", "86", 50, 50, 50, 70, None),
-        CarInfo::new("Toyota", "GT 86", 50, 50, 50, 70, None),
-        CarInfo::new("Toyota", "GT 86 RB", 55, 50, 60, 60, None),
-        CarInfo::new("Toyota", "GT 86 RB GT4", 50, 55, 50, 65, Some("caster 6.6")),
-        CarInfo::new("Toyota", "GT One  98", 55, 40, 65, 40, None),
-        CarInfo::new("Toyota", "GT One  99", 55, 40, 65, 40, None),
-        CarInfo::new(
-            "Toyota",
-            "TS 040",
-            35,
-            55,
-            40,
-            55,
-            Some("steer. ratio 14:1, caster 10.5"),
-        ),
-        CarInfo::new("Volkswagen", "Polo RX", 45, 45, 50, 55, None),
-        CarInfo::new(
-            "Audi",
-            "R18 Fuji 2016",
-            55,
-            45,
-            60,
-            55,
-            Some("steer. ratio 14.1"),
-        ),
-        CarInfo::new(
-            "Audi",
-            "R18 LeMans 2016",
-            55,
-            45,
-            60,
-            55,
-            Some("steer. ratio 14.1"),
-        ),
-        CarInfo::new("Ferrari", "512 S", 45, 45, 55, 55, None),
-        CarInfo::new("Ferrari", "512 M", 45, 45, 55, 55, None),
-        CarInfo::new(
-            "Toyota",
-            "TS 050",
-            55,
-            45,
-            60,
-            55,
-            Some("steer. ratio 14.1"),
-        ),
-        CarInfo::new(
-            "Porsche",
-            "919 Hybrid",
-            60,
-            35,
-            65,
-            45,
-            Some("steer. ratio 14.1"),
-        ),
-        CarInfo::new("Porsche", "924 Carrera GTP", 45, 25, 50, 35, None),
-        CarInfo::new("Porsche", "961 GTO", 30, 80, 35, 85, None),
-        CarInfo::new("Porsche", "917 LH", 40, 45, 45, 55, None),
-    ]
+const BUNDLED: &str = include_str!("../../resources/cars.toml");
+pub const OVERRIDE_FILE: &str = "cars.override.toml";
+
+#[derive(Deserialize)]
+struct CarsFile<T> {
+    #[serde(default = "Vec::new")]
+    car: Vec<T>,
+    #[serde(default = "Vec::new")]
+    class_defaults: Vec<ClassOverride>,
+}
+
+/// Everything known about cars, and which user overrides were not applied.
+pub struct Database {
+    pub cars: Vec<CarInfo>,
+    pub classes: Vec<ClassDefaults>,
+    pub error: Option<String>,
+}
+
+/// Override entry, anything left out is kept from bundled data.
+#[derive(Deserialize)]
+struct CarOverride {
+    name: String,
+    model: String,
+    sd_vol: Option<i32>,
+    sd_tone: Option<i32>,
+    norm_vol: Option<i32>,
+    norm_tone: Option<i32>,
+    class: Option<String>,
+    steering_ratio: Option<f32>,
+    caster: Option<f32>,
+    drivetrain: Option<Drivetrain>,
+    engine_layout: Option<EngineLayout>,
+    power_hp: Option<f32>,
+    weight_kg: Option<f32>,
+    notes: Option<String>,
+    #[serde(default = "Vec::new")]
+    aliases: Vec<String>,
+}
+
+fn bundled_file() -> CarsFile<CarInfo> {
+    toml::from_str::<CarsFile<CarInfo>>(BUNDLED).expect("Bundled cars.toml is broken")
+}
+
+/// Cars built into executable.
+pub fn bundled() -> Vec<CarInfo> {
+    bundled_file().car
+}
+
+/// Bundled cars and classes with user overrides merged on top.
+pub fn create() -> Database {
+    let bundled = bundled_file();
+    let mut cars = bundled.car;
+    let mut class_overrides = bundled.class_defaults;
+
+    let error = override_path().and_then(|path| {
+        let file = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                toml::from_str::<CarsFile<CarOverride>>(&text).map_err(|e| e.to_string())
+            });
+        match file {
+            Ok(file) => {
+                class_overrides.extend(file.class_defaults);
+                merge_all(&mut cars, file.car).map(|e| format!("{}, skipped {}", path.display(), e))
+            }
+            Err(e) => Some(format!("{} ignored, {}", path.display(), e)),
+        }
+    });
+
+    let (classes, class_error) = class_defaults(&cars, class_overrides);
+    let error = match (error, class_error) {
+        (Some(error), Some(class_error)) => Some(format!("{}; {}", error, class_error)),
+        (error, class_error) => error.or(class_error),
+    };
+    Database {
+        cars,
+        classes,
+        error,
+    }
+}
+
+/// Next to the executable, or in working directory.
+fn override_path() -> Option<PathBuf> {
+    let beside_exe = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(OVERRIDE_FILE)));
+
+    beside_exe
+        .into_iter()
+        .chain(Some(PathBuf::from(OVERRIDE_FILE)))
+        .find(|path| path.is_file())
+}
+
+/// Applies every valid entry, errors of the rest joined together.
+fn merge_all(cars: &mut Vec<CarInfo>, overrides: Vec<CarOverride>) -> Option<String> {
+    let errors: Vec<String> = overrides
+        .into_iter()
+        .filter_map(|o| merge(cars, o).err())
+        .collect();
+
+    if errors.is_empty() {
+        None
+    } else {
+        Some(errors.join("; "))
+    }
+}
+
+/// Leaves cars as they were when entry is not valid.
+fn merge(cars: &mut Vec<CarInfo>, o: CarOverride) -> Result<(), String> {
+    let existing = cars.iter().position(|car| {
+        car.name.eq_ignore_ascii_case(&o.name) && car.model.eq_ignore_ascii_case(&o.model)
+    });
+
+    match existing {
+        Some(ix) => {
+            let car = &mut cars[ix];
+            car.sd_vol = o.sd_vol.unwrap_or(car.sd_vol);
+            car.sd_tone = o.sd_tone.unwrap_or(car.sd_tone);
+            car.norm_vol = o.norm_vol.unwrap_or(car.norm_vol);
+            car.norm_tone = o.norm_tone.unwrap_or(car.norm_tone);
+            replace(&mut car.class, o.class);
+            replace(&mut car.steering_ratio, o.steering_ratio);
+            replace(&mut car.caster, o.caster);
+            replace(&mut car.drivetrain, o.drivetrain);
+            replace(&mut car.engine_layout, o.engine_layout);
+            replace(&mut car.power_hp, o.power_hp);
+            replace(&mut car.weight_kg, o.weight_kg);
+            if o.notes.is_some() {
+                car.notes = o.notes.filter(|n| !n.is_empty());
+            }
+            car.aliases.extend(o.aliases);
+        }
+        None => match (o.sd_vol, o.sd_tone, o.norm_vol, o.norm_tone) {
+            (Some(sd_vol), Some(sd_tone), Some(norm_vol), Some(norm_tone)) => cars.push(CarInfo {
+                name: o.name,
+                model: o.model,
+                sd_vol,
+                sd_tone,
+                norm_vol,
+                norm_tone,
+                class: o.class,
+                steering_ratio: o.steering_ratio,
+                caster: o.caster,
+                drivetrain: o.drivetrain,
+                engine_layout: o.engine_layout,
+                power_hp: o.power_hp,
+                weight_kg: o.weight_kg,
+                notes: o.notes.filter(|n| !n.is_empty()),
+                aliases: o.aliases,
+            }),
+            _ => {
+                return Err(format!(
+                    "new car {} {} needs sd_vol, sd_tone, norm_vol and norm_tone",
+                    o.name, o.model
+                ))
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn replace<T>(value: &mut Option<T>, new: Option<T>) {
+    if new.is_some() {
+        *value = new;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, model: &str) -> CarOverride {
+        CarOverride {
+            name: name.to_string(),
+            model: model.to_string(),
+            sd_vol: None,
+            sd_tone: None,
+            norm_vol: None,
+            norm_tone: None,
+            class: None,
+            steering_ratio: None,
+            caster: None,
+            drivetrain: None,
+            engine_layout: None,
+            power_hp: None,
+            weight_kg: None,
+            notes: None,
+            aliases: Vec::new(),
+        }
+    }
+
+    fn new_car(name: &str, model: &str) -> CarOverride {
+        CarOverride {
+            sd_vol: Some(30),
+            sd_tone: Some(40),
+            norm_vol: Some(50),
+            norm_tone: Some(60),
+            ..entry(name, model)
+        }
+    }
+
+    #[test]
+    fn merge_updates_only_given_fields() {
+        let mut cars = bundled();
+        let before = cars[0].clone();
+        let mut update = entry(&before.name.to_uppercase(), &before.model.to_lowercase());
+        update.sd_vol = Some(before.sd_vol + 1);
+        update.power_hp = Some(500f32);
+        update.notes = Some(String::new());
+        update.aliases.push("Game name".to_string());
+
+        assert_eq!(merge(&mut cars, update), Ok(()));
+        let car = &cars[0];
+        assert_eq!(car.sd_vol, before.sd_vol + 1);
+        assert_eq!(car.sd_tone, before.sd_tone);
+        assert_eq!(car.class, before.class);
+        assert_eq!(car.power_hp, Some(500f32));
+        assert_eq!(car.notes, None);
+        assert_eq!(car.aliases.last().map(|a| a.as_str()), Some("Game name"));
+        assert_eq!(cars.len(), bundled().len());
+    }
+
+    #[test]
+    fn merge_adds_new_car_with_ffb_values() {
+        let mut cars = bundled();
+        assert_eq!(merge(&mut cars, new_car("Caparo", "T1")), Ok(()));
+
+        let car = cars.last().unwrap();
+        assert_eq!(car.full_name(), "Caparo T1");
+        assert_eq!(
+            (car.sd_vol, car.sd_tone, car.norm_vol, car.norm_tone),
+            (30, 40, 50, 60)
+        );
+        assert_eq!(cars.len(), bundled().len() + 1);
+    }
+
+    #[test]
+    fn merge_rejects_new_car_without_ffb_values() {
+        let mut cars = bundled();
+        let mut incomplete = entry("Caparo", "T1");
+        incomplete.sd_vol = Some(30);
+
+        assert!(merge(&mut cars, incomplete).is_err());
+        assert_eq!(cars.len(), bundled().len());
+    }
+
+    #[test]
+    fn merge_all_keeps_valid_entries_and_reports_the_rest() {
+        let mut cars = bundled();
+        let name = cars[0].name.clone();
+        let model = cars[0].model.clone();
+        let mut update = entry(&name, &model);
+        update.class = Some("GT3".to_string());
+
+        let error = merge_all(
+            &mut cars,
+            vec![
+                entry("Caparo", "T1"),
+                update,
+                new_car("Ariel", "Atom V8"),
+                entry("Formula", "Rookie"),
+            ],
+        );
+
+        let error = error.unwrap();
+        assert!(error.contains("Caparo T1") && error.contains("Formula Rookie"));
+        assert!(!error.contains("Ariel"));
+        assert_eq!(cars[0].class.as_ref().map(|c| c.as_str()), Some("GT3"));
+        assert_eq!(cars.last().unwrap().full_name(), "Ariel Atom V8");
+        assert_eq!(cars.len(), bundled().len() + 1);
+
+        assert_eq!(merge_all(&mut cars, Vec::new()), None);
+    }
 }
@@ -0,0 +1,618 @@
+use super::CarInfo;
+use std::collections::{HashMap, HashSet};
+use strsim;
+
+/// Fuzzy matches below this are shown as unknown car.
+pub const MIN_CONFIDENCE: f64 = 0.75;
+//added to cars of the class game reports, so closely named cars of other classes lose
+const CLASS_PREFERENCE: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CarMatch {
+    /// Full name or alias, ignoring case and punctuation.
+    Exact(usize),
+    /// Closest name, with confidence 0 to 1.
+    Fuzzy(usize, f64),
+    /// Nothing close enough, best guess if there was any.
+    Unknown(Option<(usize, f64)>),
+}
+
+impl CarMatch {
+    /// Car to use, none when unknown.
+    pub fn index(&self) -> Option<usize> {
+        match *self {
+            CarMatch::Exact(ix) | CarMatch::Fuzzy(ix, _) => Some(ix),
+            CarMatch::Unknown(_) => None,
+        }
+    }
+
+    pub fn confidence(&self) -> f64 {
+        match *self {
+            CarMatch::Exact(_) => 1f64,
+            CarMatch::Fuzzy(_, confidence) | CarMatch::Unknown(Some((_, confidence))) => confidence,
+            CarMatch::Unknown(None) => 0f64,
+        }
+    }
+}
+
+/// Finds database car by game's `mCarName`, same name always gives same car.
+pub struct CarMatcher {
+    //normalized full names and aliases, in database order
+    keys: Vec<(String, usize)>,
+    exact: HashMap<String, usize>,
+    //normalized class of every car, empty if not known
+    classes: Vec<String>,
+}
+
+impl CarMatcher {
+    pub fn new(cars: &[CarInfo]) -> CarMatcher {
+        let mut keys = Vec::new();
+        for (ix, car) in cars.iter().enumerate() {
+            keys.push((normalize(&car.full_name()), ix));
+            for alias in car.aliases.iter() {
+                keys.push((normalize(alias), ix));
+            }
+        }
+
+        let mut exact = HashMap::new();
+        for &(ref key, ix) in keys.iter() {
+            //first one wins when names clash
+            exact.entry(key.clone()).or_insert(ix);
+        }
+
+        let classes = cars
+            .iter()
+            .map(|car| car.class.as_ref().map(|c| normalize(c)).unwrap_or_default())
+            .collect();
+
+        CarMatcher {
+            keys,
+            exact,
+            classes,
+        }
+    }
+
+    /// Class is the game's `mCarClassName`, may be empty.
+    pub fn find(&self, car_name: &str, class_name: &str) -> CarMatch {
+        let name = normalize(car_name);
+        let class = normalize(class_name);
+        if name.is_empty() {
+            return CarMatch::Unknown(None);
+        }
+        if let Some(ix) = self.exact.get(&name) {
+            return CarMatch::Exact(*ix);
+        }
+
+        //strictly better only, so earlier car wins a tie
+        let mut best: Option<(usize, f64, f64)> = None;
+        for &(ref key, ix) in self.keys.iter() {
+            let score = confidence(&name, key);
+            let rank = if !class.is_empty() && self.classes[ix] == class {
+                score + CLASS_PREFERENCE
+            } else {
+                score
+            };
+            if best.map(|(_, _, b)| rank > b).unwrap_or(true) {
+                best = Some((ix, score, rank));
+            }
+        }
+
+        match best {
+            Some((ix, score, _)) if score >= MIN_CONFIDENCE => CarMatch::Fuzzy(ix, score),
+            other => CarMatch::Unknown(other.map(|(ix, score, _)| (ix, score))),
+        }
+    }
+}
+
+/// Lowercase words, anything but letters and digits separates them.
+pub fn normalize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Mean of string similarity and word overlap, words missing on either side count against.
+fn confidence(a: &str, b: &str) -> f64 {
+    let words_a = a.split_whitespace().collect::<HashSet<_>>();
+    let words_b = b.split_whitespace().collect::<HashSet<_>>();
+    let common = words_a.intersection(&words_b).count();
+    let words = (words_a.len() + words_b.len()).max(1);
+
+    (strsim::jaro_winkler(a, b) + 2f64 * common as f64 / words as f64) / 2f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::data;
+    use super::*;
+
+    //mCarName of every car in the game, and the database car it is,
+    //Lotus 49 B and 49C B are left out, game names them same as the A cars
+    const GAME_NAMES: [(&str, &str, &str); 226] = [
+        ("Acura NSX", "Acura", "NSX"),
+        ("Acura NSX GT3", "Acura", "NSX GT3"),
+        ("Agajanian Watson Roadster", "Agajanian", "Watson Road"),
+        ("Aston Martin DB11", "Aston Martin", "DB 11"),
+        ("Aston Martin DBR1/300", "Aston Martin", "DBR1 300"),
+        ("Aston Martin Vantage GT12", "Aston Martin", "Vantage GT12"),
+        ("Aston Martin Vantage GT3", "Aston Martin", "Vantage GT3"),
+        ("Aston Martin Vantage GT4", "Aston Martin", "Vantage GT4"),
+        ("Aston Martin Vantage GTE", "Aston Martin", "Vantage GTE"),
+        ("Aston Martin Vulcan", "Aston Martin", "Vulcan"),
+        ("Audi 90 quattro IMSA GTO", "Audi", "90 IMSA GTO"),
+        ("Audi A1 quattro", "Audi", "A1 Quattro"),
+        ("Audi R18 (Fuji 2016)", "Audi", "R18 Fuji 2016"),
+        ("Audi R18 (Le Mans 2016)", "Audi", "R18 LeMans 2016"),
+        ("Audi R18 e-tron quattro", "Audi", "R18 etron"),
+        ("Audi R8 LMP900", "Audi", "LMP 900"),
+        ("Audi R8 LMS", "Audi", "R8 LMS"),
+        ("Audi R8 LMS (Endurance)", "Audi", "R8 LMS 24h"),
+        ("Audi R8 V10 plus", "Audi", "R8 V10 plus"),
+        ("Audi S1 EKS RX quattro", "Audi", "S1 EKS RX"),
+        ("Audi Sport quattro S1 E2", "Audi", "Sport QuattroS1"),
+        ("Audi V8 quattro DTM", "Audi", "V8 DTM"),
+        ("BAC Mono", "BAC", "Mono"),
+        (
+            "Bentley Continental GT3 (2015)",
+            "Bentley",
+            "Conti  GT3  15",
+        ),
+        (
+            "Bentley Continental GT3 (2016)",
+            "Bentley",
+            "Conti  GT3  16",
+        ),
+        (
+            "Bentley Continental GT3 (Endurance)",
+            "Bentley",
+            "Conti  GT3 24h",
+        ),
+        ("Bentley Speed 8", "Bentley", "Speed 8"),
+        ("BMW 1 Series M Coupe", "BMW", "1M Coupe"),
+        (
+            "BMW 1 Series M Coupe StanceWorks Edition",
+            "BMW",
+            "1M StanceWorks",
+        ),
+        ("BMW 2002 StanceWorks Edition", "BMW", "2002StanceWorks"),
+        ("BMW 2002 Turbo", "BMW", "2002 Turbo"),
+        ("BMW 320 TC", "BMW", "320 TC"),
+        ("BMW 320 Turbo Group 5", "BMW", "320 Turbo"),
+        ("BMW M1 Procar", "BMW", "M1 Procar"),
+        ("BMW M3 E30 Group A", "BMW", "M3 Group A"),
+        ("BMW M3 GT4", "BMW", "M3 GT4"),
+        ("BMW M6 GT3", "BMW", "M6 GT3"),
+        ("BMW M6 GTLM", "BMW", "M6 GTLM"),
+        ("BMW V12 LMR", "BMW", "V12 LMR"),
+        ("BMW Z4 GT3", "BMW", "Z4 GT3"),
+        ("Cadillac ATS-V.R GT3", "Cadillac", "ATS VR GT3"),
+        ("Caterham Seven 620R", "Caterham", "Seven 620R"),
+        ("Caterham SP/300.R", "Caterham", "SP 300 R"),
+        (
+            "Chevrolet Camaro Z/28 '69 Trans Am",
+            "Chevrolet",
+            "Camaro Z28  69",
+        ),
+        ("Chevrolet Camaro ZL1 1LE", "Chevrolet", "Camaro ZL1"),
+        ("Chevrolet Corvette C7.R", "Chevrolet", "Corvette C7 R"),
+        ("Chevrolet Corvette Z06", "Chevrolet", "Corvette Z06"),
+        ("Citroën DS3 RX", "Citroen", "DS3 RX"),
+        (
+            "Dallara IR-12 Chevrolet (Road Course)",
+            "Chevrolet",
+            "IR 12 Road",
+        ),
+        (
+            "Dallara IR-12 Chevrolet (Speedway)",
+            "Chevrolet",
+            "IR 12 Oval",
+        ),
+        ("Dallara IR-12 Honda (Road Course)", "Honda", "IR 12 Road"),
+        ("Dallara IR-12 Honda (Speedway)", "Honda", "IR 12 Oval"),
+        ("Ferrari 288 GTO", "Ferrari", "288 GTO"),
+        ("Ferrari 330 P4", "Ferrari", "330 P4"),
+        ("Ferrari 333 SP", "Ferrari", "333 SP"),
+        ("Ferrari 365 GTB4 Competizione", "Ferrari", "365 GTB 4"),
+        ("Ferrari 488 Challenge (APAC)", "Ferrari", "488 Chall  APAC"),
+        ("Ferrari 488 Challenge (EU)", "Ferrari", "488 Chall  EU"),
+        ("Ferrari 488 Challenge (NA)", "Ferrari", "488 Chall  NA"),
+        ("Ferrari 488 GT3", "Ferrari", "488 GT3"),
+        ("Ferrari 488 GTE", "Ferrari", "488 GTE"),
+        ("Ferrari 512 M", "Ferrari", "512 M"),
+        ("Ferrari 512 S", "Ferrari", "512 S"),
+        ("Ferrari Enzo", "Ferrari", "Enzo"),
+        ("Ferrari F40 LM", "Ferrari", "F40 LM"),
+        ("Ferrari F50 GT", "Ferrari", "F50 GT"),
+        ("Ferrari LaFerrari", "Ferrari", "La Ferrari"),
+        ("Ford Bronco \"Brocky\"", "Ford", "Bronco Brocky"),
+        ("Ford Escort RS1600", "Ford", "Escort 1600"),
+        ("Ford Escort RS1600 Racing", "Ford", "Esc 1600 Racing"),
+        ("Ford Escort RS1600 RX", "Ford", "Escort 1600 RX"),
+        ("Ford F-150 RTR Ultimate Funhaver", "Ford", "F 150 RTR"),
+        ("Ford Falcon FG V8 Supercar", "Ford", "Falcon FG"),
+        ("Ford Focus RS RX", "Ford", "Focus RS RX"),
+        ("Ford Fusion Stock Car", "Ford", "Fusion"),
+        ("Ford GT", "Ford", "GT"),
+        ("Ford GT LM GTE", "Ford", "GT LM GTE"),
+        ("Ford Mk IV", "Ford", "MK IV"),
+        ("Ford Mustang '66", "Ford", "Mustang  66"),
+        ("Ford Mustang '66 RTR", "Ford", "Mustang  66 RTR"),
+        ("Ford Mustang Boss 302R", "Ford", "MustangBoss302R"),
+        ("Ford Mustang Cobra TransAm", "Ford", "Mustang Cobra"),
+        ("Ford Mustang GT", "Ford", "Mustang GT"),
+        ("Ford Mustang RTR GT4", "Ford", "Mustang RTR GT4"),
+        ("Ford Mustang RTR Spec 5-D", "Ford", "Must RTRSpec 5D"),
+        ("Ford RS200 Evolution", "Ford", "RS 200 EVO"),
+        (
+            "Ford Sierra Cosworth RS500 Group A",
+            "Ford",
+            "Sierra Cosworth",
+        ),
+        ("Ford Zakspeed Capri Group 5", "Ford", "Zakspeed Capri"),
+        ("Formula A", "SMS", "Formula A"),
+        ("Formula C", "SMS", "Formula C"),
+        ("Formula Renault 3.5", "Renault", "Formula Renault"),
+        ("Formula Rookie", "SMS", "Formula R"),
+        ("Formula X", "SMS", "Formula X"),
+        ("Ginetta G40 GT5", "Ginetta", "G40 GT5"),
+        ("Ginetta G40 Junior", "Ginetta", "G40 Junior"),
+        ("Ginetta G55 GT3", "Ginetta", "G55 GT3"),
+        ("Ginetta G55 GT4", "Ginetta", "G55 GT4"),
+        ("Ginetta G57", "Ginetta", "G57"),
+        ("Ginetta LMP3", "Ginetta", "LMP3"),
+        ("Honda 2&4 Concept", "Honda", "24 Concept"),
+        ("Honda Civic Coupe GRC", "Honda", "Civic Coupe GRC"),
+        ("Honda Civic Type R", "Honda", "Civic Type R"),
+        ("Jaguar E-Type V12 Group 44", "Jaguar", "E Type Group44"),
+        ("Jaguar F-Type SVR Coupe", "Jaguar", "F Type SVR"),
+        ("Jaguar XJ220 S", "Jaguar", "XJ 220 S"),
+        ("Jaguar XJR-9", "Jaguar", "XJR 9"),
+        ("Jaguar XJR-9 LM", "Jaguar", "XJR 9 LM"),
+        ("Kart01", "SMS", "Kart"),
+        ("KTM X-Bow GT4", "KTM", "X Bow GT4"),
+        ("KTM X-Bow R", "KTM", "X Bow R"),
+        ("Lamborghini Aventador LP700-4", "Lamborghini", "Aventador"),
+        ("Lamborghini Diablo GTR", "Lamborghini", "Diablo"),
+        ("Lamborghini Huracán GT3", "Lamborghini", "Huracan GT3"),
+        ("Lamborghini Huracán LP610-4", "Lamborghini", "Huracan"),
+        (
+            "Lamborghini Huracán Super Trofeo",
+            "Lamborghini",
+            "Huracan ST",
+        ),
+        (
+            "Lamborghini Sesto Elemento",
+            "Lamborghini",
+            "Sesto Elemento",
+        ),
+        ("Lamborghini Veneno LP750-4", "Lamborghini", "Veneno"),
+        ("Ligier JS P2 Honda", "Ligier", "JS P2 Honda"),
+        ("Ligier JS P2 Judd", "Ligier", "JS P2 Judd"),
+        ("Ligier JS P2 Nissan", "Ligier", "JS P2 Nissan"),
+        ("Ligier JS P3", "Ligier", "JS P3"),
+        ("Lotus Type 25 Climax", "Lotus", "25"),
+        ("Lotus Type 38 Ford", "Lotus", "38"),
+        ("Lotus Type 40 Ford", "Lotus", "40"),
+        ("Lotus Type 49 Cosworth", "Lotus", "49  A "),
+        ("Lotus Type 49C Cosworth", "Lotus", "49C  A "),
+        ("Lotus Type 51", "Lotus", "51"),
+        ("Lotus Type 56", "Lotus", "56"),
+        ("Lotus Type 72D Cosworth", "Lotus", "72D"),
+        ("Lotus Type 78 Cosworth", "Lotus", "78"),
+        ("Lotus Type 98T Renault Turbo", "Lotus", "98T"),
+        ("Marek RP219D LMP2", "Marek", "RP 219D LMP2"),
+        ("Marek RP339H LMP1", "Marek", "RP 339H LMP1"),
+        ("Mazda MX-5 Radbul", "Mazda", "MX5 Radbul"),
+        ("McLaren 570S", "McLaren", "570 S"),
+        ("McLaren 650S GT3", "McLaren", "650 S GT3"),
+        ("McLaren 720S", "McLaren", "720 S"),
+        ("McLaren F1", "McLaren", "F1"),
+        ("McLaren F1 GTR Long Tail", "McLaren", "F1 GTR LT"),
+        ("McLaren P1", "McLaren", "P1"),
+        ("McLaren P1 GTR", "McLaren", "P1 GTR"),
+        ("Mercedes-AMG A 45 4MATIC", "Mercedes AMG", "A45 4 Matic"),
+        (
+            "Mercedes-AMG A 45 SMS-R Rallycross",
+            "Mercedes AMG",
+            "A45 RX",
+        ),
+        (
+            "Mercedes-AMG A 45 SMS-R Touring",
+            "Mercedes AMG",
+            "A45 Touring",
+        ),
+        ("Mercedes-AMG C 63 Coupé", "Mercedes AMG", "C63 Coupe"),
+        ("Mercedes-AMG GT R", "Mercedes AMG", "GTR"),
+        ("Mercedes-AMG GT3", "Mercedes AMG", "GT3"),
+        (
+            "Mercedes-Benz 190E 2.5-16 Evolution II DTM",
+            "Mercedes Benz",
+            "190E DTM",
+        ),
+        (
+            "Mercedes-Benz 300 SEL 6.8 AMG",
+            "Mercedes Benz",
+            "300 SEL 6 8",
+        ),
+        ("Mercedes-Benz 300 SL (W194)", "Mercedes Benz", "300 SL"),
+        ("Mercedes-Benz CLK-LM", "Mercedes Benz", "CLK LM"),
+        ("Mercedes-Benz Sauber C9", "Mercedes Benz", "Sauber C9"),
+        (
+            "Mercedes-Benz Sauber C9 LM",
+            "Mercedes Benz",
+            "Sauber C9 LM",
+        ),
+        ("Mercedes-Benz SLS AMG GT3", "Mercedes Benz", "SLS AMG GT3"),
+        ("MINI Countryman RX", "Mini", "Countryman RX"),
+        (
+            "Mitsubishi Lancer Evolution IX FQ360",
+            "Mitsubishi",
+            "Lancer EVO IX",
+        ),
+        (
+            "Mitsubishi Lancer Evolution VI SVA",
+            "Mitsubishi",
+            "Lancer EVO SVA",
+        ),
+        (
+            "Mitsubishi Lancer Evolution VI T.M.E.",
+            "Mitsubishi",
+            "Lancer EVO TME",
+        ),
+        (
+            "Mitsubishi Lancer Evolution X FQ400",
+            "Mitsubishi",
+            "Lancer EVO X",
+        ),
+        ("Nissan 240ZG GTS", "Nissan", "240 ZG GTS"),
+        ("Nissan 280ZX IMSA GTX", "Nissan", "280 ZX IMSA"),
+        ("Nissan 300ZX Turbo IMSA", "Nissan", "300 ZX IMSA"),
+        ("Nissan 300ZX Turbo LM", "Nissan", "300 ZX LM"),
+        ("Nissan GT-R Nismo", "Nissan", "GTR Nismo"),
+        ("Nissan GT-R Nismo GT3", "Nissan", "GTR GT3"),
+        ("Nissan GTP ZX-Turbo", "Nissan", "GTP ZX T"),
+        ("Nissan R390 GT1", "Nissan", "R390 GT1"),
+        ("Nissan R89C", "Nissan", "R89C"),
+        ("Nissan R89C LM", "Nissan", "R89C LM"),
+        ("Nissan Skyline GT-R (R32)", "Nissan", "Skyline RS 32"),
+        ("Nissan Skyline GT-R SMS-R", "Nissan", "Skyline SMS R"),
+        (
+            "Nissan Skyline Super Silhouette",
+            "Nissan",
+            "Skyl Silhouette",
+        ),
+        ("Olsbergs MSE RX Supercar Lite", "Olsberg", "RX Supercar"),
+        ("Opel Astra TCR", "Opel", "Astra"),
+        ("Oreca 03 Nissan", "Oreca", "LMP 2"),
+        ("Pagani Huayra BC", "Pagani", "Huayra BC"),
+        ("Pagani Zonda Cinque Roadster", "Pagani", "Zonda Cinque"),
+        ("Pagani Zonda Revolución", "Pagani", "Zonda Revo "),
+        ("Panoz Esperante GTR-1", "Panoz", "Esperante GT1"),
+        ("Porsche 908/03 Spyder", "Porsche", "908 03 Spyder"),
+        ("Porsche 911 Carrera RSR 2.8", "Porsche", "911 Carrera RSR"),
+        ("Porsche 911 GT1-98", "Porsche", "911 GT 98"),
+        ("Porsche 911 GT3 R", "Porsche", "911 GT R"),
+        ("Porsche 911 GT3 R Endurance", "Porsche", "911 GT R 24h"),
+        ("Porsche 911 GT3 RS", "Porsche", "911 GT3 RS"),
+        ("Porsche 911 RSR", "Porsche", "911 RSR"),
+        ("Porsche 917 LH", "Porsche", "917 LH"),
+        ("Porsche 917/10", "Porsche", "917 10"),
+        ("Porsche 917K", "Porsche", "917 K"),
+        ("Porsche 918 Spyder Weissach", "Porsche", "918 Spyder"),
+        ("Porsche 919 Hybrid", "Porsche", "919 Hybrid"),
+        ("Porsche 924 Carrera GTP", "Porsche", "924 Carrera GTP"),
+        ("Porsche 935/77", "Porsche", "935 77"),
+        ("Porsche 935/78 Moby Dick", "Porsche", "935 78"),
+        ("Porsche 935/78-81", "Porsche", "935 78 81"),
+        ("Porsche 935/80", "Porsche", "935 80"),
+        ("Porsche 936 Spyder", "Porsche", "936 Spyder"),
+        ("Porsche 959 S", "Porsche", "959 S"),
+        ("Porsche 961", "Porsche", "961 GTO"),
+        ("Porsche 962C", "Porsche", "962C"),
+        ("Porsche 962C Langheck", "Porsche", "962C LH"),
+        ("Porsche Carrera GT", "Porsche", "Carrera GT"),
+        (
+            "Porsche Cayman GT4 Clubsport MR",
+            "Porsche",
+            "Cayman GT4 MR",
+        ),
+        ("Radical RXC Turbo", "Radical", "RXC Turbo"),
+        ("Radical SR3-RS", "Radical", "SR3 RS"),
+        ("Radical SR8-RX", "Radical", "SR8 RX"),
+        ("Renault 5 Maxi Turbo", "Renault", "5 Maxi Turbo"),
+        ("Renault Alpine A442B", "Renault", "A 442 B"),
+        ("Renault Clio Cup", "Renault", "Clio Cup"),
+        (
+            "Renault Mégane R.S. 275 Trophy-R",
+            "Renault",
+            "Megane RS 275",
+        ),
+        (
+            "Renault Mégane R.S. SMS-R Touring",
+            "Renault",
+            "Megane Touring",
+        ),
+        ("Renault Mégane RX", "Renault", "Megane RX"),
+        ("Renault Mégane Trophy V6", "Renault", "Megane V6"),
+        ("Renault R.S. 01", "Renault", "RS 01"),
+        ("Renault R.S. 01 GT3", "Renault", "RS 01 GT3"),
+        ("RWD P20 LMP2", "RWD", "P20 LMP2"),
+        ("RWD P30 LMP1", "RWD", "P30 LMP1"),
+        ("Toyota 86", "Toyota", "86"),
+        ("Toyota GT-One (1998)", "Toyota", "GT One  98"),
+        ("Toyota GT-One (1999)", "Toyota", "GT One  99"),
+        ("Toyota GT86", "Toyota", "GT 86"),
+        ("Toyota GT86 Rocket Bunny", "Toyota", "GT 86 RB"),
+        ("Toyota GT86 Rocket Bunny GT4", "Toyota", "GT 86 RB GT4"),
+        ("Toyota TS040 Hybrid", "Toyota", "TS 040"),
+        ("Toyota TS050 Hybrid", "Toyota", "TS 050"),
+        ("Volkswagen Polo RX", "Volkswagen", "Polo RX"),
+    ];
+
+    fn car(name: &str, model: &str) -> CarInfo {
+        CarInfo {
+            name: name.to_string(),
+            model: model.to_string(),
+            sd_vol: 50,
+            sd_tone: 50,
+            norm_vol: 50,
+            norm_tone: 50,
+            class: None,
+            steering_ratio: None,
+            caster: None,
+            drivetrain: None,
+            engine_layout: None,
+            power_hp: None,
+            weight_kg: None,
+            notes: None,
+            aliases: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn game_names_are_unique() {
+        let cars = data::bundled();
+        let mut seen = HashMap::new();
+        for (ix, car) in cars.iter().enumerate() {
+            if let Some(other) = seen.insert(normalize(&car.full_name()), ix) {
+                panic!(
+                    "{} and {} have same name",
+                    cars[other].full_name(),
+                    car.full_name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_game_name_matches_exactly() {
+        let cars = data::bundled();
+        let matcher = CarMatcher::new(&cars);
+        for (ix, car) in cars.iter().enumerate() {
+            assert_eq!(
+                matcher.find(&car.full_name(), ""),
+                CarMatch::Exact(ix),
+                "{}",
+                car.full_name()
+            );
+            for alias in car.aliases.iter() {
+                assert_eq!(matcher.find(alias, ""), CarMatch::Exact(ix), "{}", alias);
+            }
+        }
+    }
+
+    #[test]
+    fn game_names_find_their_cars() {
+        let cars = data::bundled();
+        let matcher = CarMatcher::new(&cars);
+        for &(game_name, name, model) in GAME_NAMES.iter() {
+            match matcher.find(game_name, "") {
+                CarMatch::Exact(ix) => assert_eq!(
+                    (cars[ix].name.as_str(), cars[ix].model.as_str()),
+                    (name, model),
+                    "{}",
+                    game_name
+                ),
+                other => panic!("{} gave {:?}", game_name, other),
+            }
+        }
+    }
+
+    #[test]
+    fn case_and_punctuation_are_ignored() {
+        let cars = data::bundled();
+        let matcher = CarMatcher::new(&cars);
+        for (ix, car) in cars.iter().enumerate() {
+            let name = format!(
+                " {}-{} ",
+                car.name.to_uppercase(),
+                car.model.replace(' ', "_")
+            );
+            assert_eq!(matcher.find(&name, ""), CarMatch::Exact(ix), "{}", name);
+        }
+    }
+
+    #[test]
+    fn extra_words_match_with_confidence() {
+        let cars = data::bundled();
+        let matcher = CarMatcher::new(&cars);
+        for (ix, car) in cars.iter().enumerate() {
+            let name = format!("{} 2017", car.full_name());
+            let class = car.class.clone().unwrap_or_default();
+            match matcher.find(&name, &class) {
+                CarMatch::Fuzzy(found, confidence) => {
+                    assert_eq!(found, ix, "{} found {}", name, cars[found].full_name());
+                    assert!(confidence >= MIN_CONFIDENCE && confidence < 1f64);
+                }
+                other => panic!("{} gave {:?}", name, other),
+            }
+        }
+    }
+
+    #[test]
+    fn unrelated_names_are_unknown() {
+        let matcher = CarMatcher::new(&data::bundled());
+        for name in [
+            "",
+            "  ",
+            "Zephyr Q7 Roadster",
+            "Formula Nimbus",
+            "Veltro 48 Stradale",
+        ]
+        .iter()
+        {
+            match matcher.find(name, "") {
+                CarMatch::Unknown(_) => (),
+                other => panic!("{} gave {:?}", name, other),
+            }
+        }
+    }
+
+    #[test]
+    fn aliases_match_exactly() {
+        let mut aliased = car("Bentley", "Conti GT3 15");
+        aliased
+            .aliases
+            .push("Bentley Continental GT3 (2015)".to_string());
+        let matcher = CarMatcher::new(&[car("Bentley", "Conti GT3 16"), aliased]);
+
+        assert_eq!(
+            matcher.find("bentley continental gt3 2015", ""),
+            CarMatch::Exact(1)
+        );
+    }
+
+    #[test]
+    fn class_decides_between_close_names() {
+        let mut gt3 = car("Ferrari", "488 GT3");
+        gt3.class = Some("GT3".to_string());
+        let mut gte = car("Ferrari", "488 GTE");
+        gte.class = Some("GTE".to_string());
+        let matcher = CarMatcher::new(&[gt3, gte]);
+
+        assert_eq!(matcher.find("Ferrari 488", "GT3").index(), Some(0));
+        assert_eq!(matcher.find("Ferrari 488", "gte").index(), Some(1));
+        assert_eq!(
+            matcher.find("Ferrari 488", "GTE").confidence(),
+            matcher.find("Ferrari 488", "").confidence()
+        );
+        match matcher.find("Caparo T1", "GT3") {
+            CarMatch::Unknown(_) => (),
+            other => panic!("class made {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ties_go_to_first_car() {
+        let matcher = CarMatcher::new(&[car("Lotus", "49 A"), car("Lotus", "49 A")]);
+
+        assert_eq!(matcher.find("Lotus 49 A", ""), CarMatch::Exact(0));
+        let fuzzy = matcher.find("Lotus 49 A 1967", "");
+        assert_eq!(fuzzy.index(), Some(0));
+        assert_eq!(fuzzy, matcher.find("Lotus 49 A 1967", ""));
+    }
+}
@@ -1,90 +1,216 @@
+mod classes;
 pub mod data;
+mod matcher;
+
+pub use self::classes::*;
+pub use self::matcher::*;
 
 use ggez::graphics::*;
 use ggez::*;
-use std::cmp::Ordering;
-use std::collections::HashSet;
-use strsim;
 
 use app;
 
 pub struct AllCarsData {
     data: Vec<CarInfoCached>,
-    current_car_ix: Option<usize>,
+    classes: Vec<ClassDefaults>,
+    matcher: CarMatcher,
+    current: Option<CarMatch>,
+    game_name: String,
+    game_class: String,
+    status: Option<Text>,
     font: Font,
+    load_error: Option<String>,
 }
 
 impl AllCarsData {
     pub fn new(font: Font) -> AllCarsData {
-        let data = data::create().into_iter().map(|d| d.with_cache()).collect();
+        let database = data::create();
+        let matcher = CarMatcher::new(&database.cars);
+        let data = database.cars.into_iter().map(|d| d.with_cache()).collect();
 
         AllCarsData {
             data,
-            current_car_ix: None,
+            classes: database.classes,
+            matcher,
+            current: None,
+            game_name: String::new(),
+            game_class: String::new(),
+            status: None,
             font,
+            load_error: database.error,
         }
     }
 
-    pub fn set(&mut self, car_name: &String) {
-        let car_info = self
-            .data
-            .iter()
-            .enumerate()
-            .max_by(|(_, info_l), (_, info_r)| {
-                let sl = strsim::jaro_winkler(&info_l.full_name, &car_name);
-                let sr = strsim::jaro_winkler(&info_r.full_name, &car_name);
+    /// Why user overrides, or some of their entries, were not applied.
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_ref().map(|e| e.as_str())
+    }
 
-                if sl > sr {
-                    Ordering::Greater
-                } else if sl == sr {
-                    inclusions(&car_name, &info_l.full_name)
-                        .cmp(&inclusions(&car_name, &info_r.full_name))
-                } else {
-                    Ordering::Less
-                }
-            })
-            .unwrap();
+    /// Class is the game's `mCarClassName`, helps to pick between similar names.
+    pub fn set(&mut self, car_name: &str, class_name: &str) {
+        self.current = Some(self.matcher.find(car_name, class_name));
+        self.game_name = car_name.to_string();
+        self.game_class = class_name.to_string();
+        self.status = None;
+    }
 
-        self.current_car_ix = Some(car_info.0);
+    /// How the car was found, none before first one.
+    pub fn current_match(&self) -> Option<CarMatch> {
+        self.current
     }
 
     pub fn good_name(&self) -> Option<String> {
-        if let Some(ix) = self.current_car_ix {
-            Some(self.data[ix].full_name.clone())
-        } else {
-            None
+        self.current().map(|car| car.full_name())
+    }
+
+    pub fn current(&self) -> Option<&CarInfo> {
+        self.current
+            .and_then(|m| m.index())
+            .map(|ix| &self.data[ix].car_info)
+    }
+
+    /// Class of current car from database, or as game reports it.
+    pub fn current_class(&self) -> Option<&ClassDefaults> {
+        let name = self
+            .current()
+            .and_then(|car| car.class.as_ref())
+            .unwrap_or(&self.game_class);
+
+        self.classes.iter().find(|c| same_class(&c.name, name))
+    }
+
+    /// All classes by name.
+    pub fn classes(&self) -> &[ClassDefaults] {
+        &self.classes
+    }
+
+    pub fn class_cars(&self, class: &ClassDefaults) -> Vec<&CarInfo> {
+        class
+            .cars
+            .iter()
+            .map(|ix| &self.data[*ix].car_info)
+            .collect()
+    }
+
+    /// How car was found, nothing when it is certain.
+    fn status_line(&self, current: CarMatch) -> Option<String> {
+        let class = self.current_class();
+        match current {
+            CarMatch::Exact(_) => None,
+            CarMatch::Fuzzy(_, confidence) => Some(format!(
+                "guessed from {}, {:.0}% match",
+                self.game_name,
+                confidence * 100f64
+            )),
+            CarMatch::Unknown(_) => Some(match class {
+                Some(class) => format!(
+                    "unknown car {}, {} defaults SD: {}/{}, REG: {}/{}",
+                    self.game_name,
+                    class.name,
+                    class.sd_vol,
+                    class.sd_tone,
+                    class.norm_vol,
+                    class.norm_tone
+                ),
+                None => format!("unknown car {}, no ffb settings", self.game_name),
+            }),
         }
     }
 
     pub fn draw_from_right(&mut self, ctx: &mut Context, dest: &Point2) -> GameResult<()> {
-        if let Some(ix) = self.current_car_ix {
+        let current = match self.current {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+
+        let mut y = dest.y + app::FONT_OFFSET;
+        if let Some(ix) = current.index() {
             let text: &Text = self.data[ix].get_text(ctx, &self.font);
-            let dest = Point2::new(
-                dest.x - text.width() as f32 - app::FONT_OFFSET,
-                dest.y + app::FONT_OFFSET,
-            );
-            text.draw(ctx, dest, 0f32)?
+            let dest = Point2::new(dest.x - text.width() as f32 - app::FONT_OFFSET, y);
+            text.draw(ctx, dest, 0f32)?;
+            y += text.height() as f32;
+        }
+
+        if self.status.is_none() {
+            let status = self.status_line(current);
+            self.status = match status {
+                Some(status) => Some(Text::new(ctx, &status, &self.font)?),
+                None => None,
+            };
+        }
+
+        if let Some(ref status) = self.status {
+            let dest = Point2::new(dest.x - status.width() as f32 - app::FONT_OFFSET, y);
+            status.draw(ctx, dest, 0f32)?;
         }
 
         Ok(())
     }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Drivetrain {
+    Fwd,
+    Rwd,
+    Awd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineLayout {
+    Front,
+    Mid,
+    Rear,
+}
+
+impl Drivetrain {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Drivetrain::Fwd => "FWD",
+            Drivetrain::Rwd => "RWD",
+            Drivetrain::Awd => "AWD",
+        }
+    }
+}
+
+impl EngineLayout {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            EngineLayout::Front => "front",
+            EngineLayout::Mid => "mid",
+            EngineLayout::Rear => "rear",
+        }
+    }
+}
+
+/// One car of `resources/cars.toml`, anything optional may be unknown.
+#[derive(Clone, Deserialize)]
 pub struct CarInfo {
-    name: &'static str,
-    model: &'static str,
-    sd_vol: i32,
-    sd_tone: i32,
-    norm_vol: i32,
-    norm_tone: i32,
-    ads: Option<&'static str>,
+    pub name: String,
+    pub model: String,
+    pub sd_vol: i32,
+    pub sd_tone: i32,
+    pub norm_vol: i32,
+    pub norm_tone: i32,
+    pub class: Option<String>,
+    /// n for n:1
+    pub steering_ratio: Option<f32>,
+    /// Degrees.
+    pub caster: Option<f32>,
+    pub drivetrain: Option<Drivetrain>,
+    pub engine_layout: Option<EngineLayout>,
+    pub power_hp: Option<f32>,
+    pub weight_kg: Option<f32>,
+    pub notes: Option<String>,
+    /// Other names game uses for this car.
+    #[serde(default = "Vec::new")]
+    pub aliases: Vec<String>,
 }
 
 #[derive(Clone)]
 struct CarInfoCached {
     car_info: CarInfo,
-    full_name: String,
     text: Option<Text>,
 }
 
@@ -93,14 +219,15 @@ impl CarInfoCached {
         if let Some(ref txt) = self.text {
             &txt
         } else {
+            let setup = self.car_info.setup_notes();
             let rates = format!(
                 "SD: {}/{}, REG: {}/{}{}{}",
                 self.car_info.sd_vol,
                 self.car_info.sd_tone,
                 self.car_info.norm_vol,
                 self.car_info.norm_tone,
-                self.car_info.ads.map(|_| " ".into()).unwrap_or(""),
-                self.car_info.ads.unwrap_or(""),
+                if setup.is_empty() { "" } else { " " },
+                setup,
             );
 
             let text = Text::new(ctx, &rates, font).unwrap();
@@ -110,54 +237,43 @@ impl CarInfoCached {
     }
 }
 
+/// Horsepower per tonne, none unless both are known.
+pub fn hp_per_tonne(power_hp: Option<f32>, weight_kg: Option<f32>) -> Option<f32> {
+    match (power_hp, weight_kg) {
+        (Some(power), Some(weight)) if weight > 0f32 => Some(power / weight * 1000f32),
+        _ => None,
+    }
+}
+
 impl CarInfo {
-    fn new(
-        name: &'static str,
-        model: &'static str,
-        sd_vol: i32,
-        sd_tone: i32,
-        norm_vol: i32,
-        norm_tone: i32,
-        ads: Option<&'static str>,
-    ) -> CarInfo {
-        CarInfo {
-            name,
-            model,
-            sd_vol,
-            sd_tone,
-            norm_vol,
-            norm_tone,
-            ads,
+    pub fn full_name(&self) -> String {
+        format!("{} {}", self.name, self.model)
+    }
+
+    /// Steering ratio, caster and notes, as shown next to ffb settings.
+    pub fn setup_notes(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(ratio) = self.steering_ratio {
+            parts.push(format!("steer. ratio {}:1", ratio));
+        }
+        if let Some(caster) = self.caster {
+            parts.push(format!("caster {:.1}", caster));
         }
+        if let Some(ref notes) = self.notes {
+            parts.push(notes.clone());
+        }
+        parts.join(", ")
     }
 
-    fn with_cache(self) -> CarInfoCached {
-        let full_name = format!("{} {}", self.name, self.model);
+    /// Published horsepower per tonne.
+    pub fn power_to_weight(&self) -> Option<f32> {
+        hp_per_tonne(self.power_hp, self.weight_kg)
+    }
 
+    fn with_cache(self) -> CarInfoCached {
         CarInfoCached {
             car_info: self,
-            full_name,
             text: None,
         }
     }
 }
-
-fn inclusions(original: &str, check: &str) -> usize {
-    let clean = original
-        .chars()
-        .map(|x| {
-            if x.is_alphanumeric() || x.is_whitespace() {
-                x
-            } else {
-                ' '
-            }
-        })
-        .collect::<String>();
-
-    let checks = check.split_whitespace().collect::<HashSet<_>>();
-
-    clean
-        .split_whitespace()
-        .filter(|x| checks.contains(x))
-        .count()
-}
@@ -0,0 +1,408 @@
+use definitions::*;
+use source::*;
+use std::fmt;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+/// Enum read from shared memory, raw value is kept when this version does not know it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Checked<T> {
+    Known(T),
+    Unknown(u32),
+}
+
+impl<T> Checked<T> {
+    pub fn known(self) -> Option<T> {
+        match self {
+            Checked::Known(value) => Some(value),
+            Checked::Unknown(_) => None,
+        }
+    }
+}
+
+/// Enums the game writes as `u32`, numbered from zero in header order.
+pub trait RawEnum: Sized + Copy + 'static {
+    const VALUES: &'static [Self];
+
+    fn checked(raw: u32) -> Checked<Self> {
+        match Self::VALUES.get(raw as usize) {
+            Some(value) => Checked::Known(*value),
+            None => Checked::Unknown(raw),
+        }
+    }
+}
+
+impl RawEnum for GameState {
+    const VALUES: &'static [GameState] = &[
+        GameState::GAME_EXITED,
+        GameState::GAME_FRONT_END,
+        GameState::GAME_INGAME_PLAYING,
+        GameState::GAME_INGAME_PAUSED,
+        GameState::GAME_INGAME_INMENU_TIME_TICKING,
+        GameState::GAME_INGAME_RESTARTING,
+        GameState::GAME_INGAME_REPLAY,
+        GameState::GAME_FRONT_END_REPLAY,
+    ];
+}
+
+impl RawEnum for SessionState {
+    const VALUES: &'static [SessionState] = &[
+        SessionState::SESSION_INVALID,
+        SessionState::SESSION_PRACTICE,
+        SessionState::SESSION_TEST,
+        SessionState::SESSION_QUALIFY,
+        SessionState::SESSION_FORMATION_LAP,
+        SessionState::SESSION_RACE,
+        SessionState::SESSION_TIME_ATTACK,
+    ];
+}
+
+impl RawEnum for RaceState {
+    const VALUES: &'static [RaceState] = &[
+        RaceState::RACESTATE_INVALID,
+        RaceState::RACESTATE_NOT_STARTED,
+        RaceState::RACESTATE_RACING,
+        RaceState::RACESTATE_FINISHED,
+        RaceState::RACESTATE_DISQUALIFIED,
+        RaceState::RACESTATE_RETIRED,
+        RaceState::RACESTATE_DNF,
+    ];
+}
+
+impl RawEnum for FlagColour {
+    const VALUES: &'static [FlagColour] = &[
+        FlagColour::FLAG_COLOUR_NONE,
+        FlagColour::FLAG_COLOUR_GREEN,
+        FlagColour::FLAG_COLOUR_BLUE,
+        FlagColour::FLAG_COLOUR_WHITE_SLOW_CAR,
+        FlagColour::FLAG_COLOUR_WHITE_FINAL_LAP,
+        FlagColour::FLAG_COLOUR_RED,
+        FlagColour::FLAG_COLOUR_YELLOW,
+        FlagColour::FLAG_COLOUR_DOUBLE_YELLOW,
+        FlagColour::FLAG_COLOUR_BLACK_AND_WHITE,
+        FlagColour::FLAG_COLOUR_BLACK_ORANGE_CIRCLE,
+        FlagColour::FLAG_COLOUR_BLACK,
+        FlagColour::FLAG_COLOUR_CHEQUERED,
+    ];
+}
+
+impl RawEnum for FlagReason {
+    const VALUES: &'static [FlagReason] = &[
+        FlagReason::FLAG_REASON_NONE,
+        FlagReason::FLAG_REASON_SOLO_CRASH,
+        FlagReason::FLAG_REASON_VEHICLE_CRASH,
+        FlagReason::FLAG_REASON_VEHICLE_OBSTRUCTION,
+    ];
+}
+
+impl RawEnum for PitMode {
+    const VALUES: &'static [PitMode] = &[
+        PitMode::PIT_MODE_NONE,
+        PitMode::PIT_MODE_DRIVING_INTO_PITS,
+        PitMode::PIT_MODE_IN_PIT,
+        PitMode::PIT_MODE_DRIVING_OUT_OF_PITS,
+        PitMode::PIT_MODE_IN_GARAGE,
+        PitMode::PIT_MODE_DRIVING_OUT_OF_GARAGE,
+    ];
+}
+
+impl RawEnum for PitSchedule {
+    const VALUES: &'static [PitSchedule] = &[
+        PitSchedule::PIT_SCHEDULE_NONE,
+        PitSchedule::PIT_SCHEDULE_PLAYER_REQUESTED,
+        PitSchedule::PIT_SCHEDULE_ENGINEER_REQUESTED,
+        PitSchedule::PIT_SCHEDULE_DAMAGE_REQUESTED,
+        PitSchedule::PIT_SCHEDULE_MANDATORY,
+        PitSchedule::PIT_SCHEDULE_DRIVE_THROUGH,
+        PitSchedule::PIT_SCHEDULE_STOP_GO,
+        PitSchedule::PIT_SCHEDULE_PITSPOT_OCCUPIED,
+    ];
+}
+
+impl RawEnum for CrashDamageState {
+    const VALUES: &'static [CrashDamageState] = &[
+        CrashDamageState::CRASH_DAMAGE_NONE,
+        CrashDamageState::CRASH_DAMAGE_OFFTRACK,
+        CrashDamageState::CRASH_DAMAGE_LARGE_PROP,
+        CrashDamageState::CRASH_DAMAGE_SPINNING,
+        CrashDamageState::CRASH_DAMAGE_ROLLING,
+    ];
+}
+
+/// Something in shared memory that does not look like what definitions expect.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anomaly {
+    Version(u32),
+    UnknownEnum(&'static str, u32),
+    InvalidBool(&'static str, u8),
+    OutOfRange(&'static str, i32),
+    NotFinite(&'static str),
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Anomaly::Version(version) => {
+                write!(
+                    f,
+                    "version {}, known are {}",
+                    version,
+                    MemoryLayout::known_versions()
+                )
+            }
+            Anomaly::UnknownEnum(field, raw) => write!(f, "{} unknown({})", field, raw),
+            Anomaly::InvalidBool(field, raw) => write!(f, "{} is not a bool ({})", field, raw),
+            Anomaly::OutOfRange(field, value) => write!(f, "{} out of range ({})", field, value),
+            Anomaly::NotFinite(field) => write!(f, "{} is not a number", field),
+        }
+    }
+}
+
+/// Addresses of bytes between end of a field and start of next one.
+fn gap<A, B>(field: &A, next: &B) -> (usize, usize) {
+    (
+        field as *const A as usize + mem::size_of::<A>(),
+        next as *const B as usize,
+    )
+}
+
+impl ParticipantInfo {
+    pub fn is_active(&self) -> bool {
+        self.mIsActive != 0
+    }
+}
+
+impl SharedMemory {
+    /// Frame before anything is read, zero is valid for every field.
+    pub fn zeroed() -> SharedMemory {
+        unsafe { mem::zeroed() }
+    }
+
+    /// Zeroes bytes compiler put between fields, `clone()` leaves them undefined.
+    pub fn zero_padding(&mut self) {
+        let mut gaps = vec![
+            gap(&self.mLapInvalidated, &self.mBestLapTime),
+            gap(&self.mAntiLockActive, &self.mLastOpponentCollisionIndex),
+            gap(&self.mBoostActive, &self.mBoostAmount),
+        ];
+        for info in self.mParticipantInfo.data.iter() {
+            gaps.push(gap(&info.mName, &info.mWorldPosition));
+        }
+
+        let base = self as *mut SharedMemory as *mut u8;
+        for (start, end) in gaps {
+            unsafe {
+                let offset = start - base as usize;
+                ptr::write_bytes(base.offset(offset as isize), 0, end - start);
+            }
+        }
+    }
+
+    /// Raw frame as the game wrote it, padding included, see `zero_padding`.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            slice::from_raw_parts(
+                self as *const SharedMemory as *const u8,
+                mem::size_of::<SharedMemory>(),
+            )
+        }
+    }
+
+    /// Frame from raw bytes, any bytes of right length are a valid one.
+    pub fn from_bytes(bytes: &[u8]) -> Option<SharedMemory> {
+        if bytes.len() == mem::size_of::<SharedMemory>() {
+            Some(unsafe { ptr::read_unaligned(bytes.as_ptr() as *const SharedMemory) })
+        } else {
+            None
+        }
+    }
+
+    pub fn game_state(&self) -> Checked<GameState> {
+        GameState::checked(self.mGameState)
+    }
+
+    pub fn session_state(&self) -> Checked<SessionState> {
+        SessionState::checked(self.mSessionState)
+    }
+
+    pub fn race_state(&self) -> Checked<RaceState> {
+        RaceState::checked(self.mRaceState)
+    }
+
+    pub fn pit_mode(&self) -> Checked<PitMode> {
+        PitMode::checked(self.mPitMode)
+    }
+
+    pub fn pit_schedule(&self) -> Checked<PitSchedule> {
+        PitSchedule::checked(self.mPitSchedule)
+    }
+
+    pub fn crash_state(&self) -> Checked<CrashDamageState> {
+        CrashDamageState::checked(self.mCrashState)
+    }
+
+    /// Track as shown to player, translated names when game gives them.
+    pub fn track_display_name(&self) -> String {
+        let location = preferred_name(&self.mTranslatedTrackLocation, &self.mTrackLocation);
+        let variation = preferred_name(&self.mTranslatedTrackVariation, &self.mTrackVariation);
+
+        if variation.is_empty() || location.ends_with(&variation) {
+            location
+        } else {
+            format!("{} {}", location, variation)
+        }
+    }
+
+    /// Car name without padding, game has no translated ones.
+    pub fn car_display_name(&self) -> String {
+        display_name(&self.mCarName)
+    }
+
+    pub fn car_class_display_name(&self) -> String {
+        display_name(&self.mCarClassName)
+    }
+
+    pub fn lap_invalidated(&self) -> bool {
+        self.mLapInvalidated != 0
+    }
+
+    pub fn anti_lock_active(&self) -> bool {
+        self.mAntiLockActive != 0
+    }
+
+    pub fn boost_active(&self) -> bool {
+        self.mBoostActive != 0
+    }
+
+    /// Participant slots in use, never more than arrays hold.
+    pub fn participant_count(&self) -> usize {
+        (self.mNumParticipants.max(0) as usize).min(STORED_PARTICIPANTS_MAX)
+    }
+
+    /// Index of viewed participant, if it is set and points to a slot in use.
+    pub fn viewed_participant(&self) -> Option<usize> {
+        let ix = self.mViewedParticipantIndex;
+        if ix >= 0 && (ix as usize) < self.participant_count() {
+            Some(ix as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Every value that is out of what the header allows, a game update most likely.
+    pub fn anomalies(&self) -> Vec<Anomaly> {
+        let mut found = Vec::new();
+
+        if MemoryLayout::from_version(self.mVersion).is_none() {
+            found.push(Anomaly::Version(self.mVersion));
+        }
+
+        check_enum::<GameState>(&mut found, "mGameState", &[self.mGameState]);
+        check_enum::<SessionState>(&mut found, "mSessionState", &[self.mSessionState]);
+        check_enum::<RaceState>(&mut found, "mRaceState", &[self.mRaceState]);
+        check_enum::<FlagColour>(&mut found, "mHighestFlagColour", &[self.mHighestFlagColour]);
+        check_enum::<FlagReason>(&mut found, "mHighestFlagReason", &[self.mHighestFlagReason]);
+        check_enum::<PitMode>(&mut found, "mPitMode", &[self.mPitMode]);
+        check_enum::<PitSchedule>(&mut found, "mPitSchedule", &[self.mPitSchedule]);
+        check_enum::<CrashDamageState>(&mut found, "mCrashState", &[self.mCrashState]);
+
+        check_bool(&mut found, "mLapInvalidated", &[self.mLapInvalidated]);
+        check_bool(&mut found, "mAntiLockActive", &[self.mAntiLockActive]);
+        check_bool(&mut found, "mBoostActive", &[self.mBoostActive]);
+
+        let participants = self.mNumParticipants;
+        if participants < -1 || participants > STORED_PARTICIPANTS_MAX as i32 {
+            found.push(Anomaly::OutOfRange("mNumParticipants", participants));
+        }
+
+        let viewed = self.mViewedParticipantIndex;
+        if viewed < -1 || (viewed >= 0 && self.viewed_participant().is_none()) {
+            found.push(Anomaly::OutOfRange("mViewedParticipantIndex", viewed));
+        }
+
+        //unused slots may hold anything
+        let count = self.participant_count();
+        let active: Vec<u8> = self.mParticipantInfo.data[..count]
+            .iter()
+            .map(|p| p.mIsActive)
+            .collect();
+        check_bool(&mut found, "mIsActive", &active);
+        check_bool(
+            &mut found,
+            "mLapsInvalidated",
+            &self.mLapsInvalidated.data[..count],
+        );
+        check_enum::<RaceState>(&mut found, "mRaceStates", &self.mRaceStates.data[..count]);
+        check_enum::<PitMode>(&mut found, "mPitModes", &self.mPitModes.data[..count]);
+        check_enum::<PitSchedule>(
+            &mut found,
+            "mPitSchedules",
+            &self.mPitSchedules.data[..count],
+        );
+        check_enum::<FlagColour>(
+            &mut found,
+            "mHighestFlagColours",
+            &self.mHighestFlagColours.data[..count],
+        );
+        check_enum::<FlagReason>(
+            &mut found,
+            "mHighestFlagReasons",
+            &self.mHighestFlagReasons.data[..count],
+        );
+
+        let floats = [
+            ("mSpeed", self.mSpeed),
+            ("mRpm", self.mRpm),
+            ("mEngineTorque", self.mEngineTorque),
+            ("mTrackLength", self.mTrackLength),
+            ("mFuelLevel", self.mFuelLevel),
+            ("mLocalAcceleration", self.mLocalAcceleration.length()),
+            ("mLocalVelocity", self.mLocalVelocity.length()),
+            (
+                "mSuspensionTravel",
+                self.mSuspensionTravel.data.iter().sum::<f32>(),
+            ),
+        ];
+        for &(field, value) in floats.iter() {
+            if !value.is_finite() {
+                found.push(Anomaly::NotFinite(field));
+            }
+        }
+
+        found
+    }
+}
+
+/// Name without padding and repeated whitespace.
+pub fn display_name(name: &PCString) -> String {
+    name.to_string()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn preferred_name(preferred: &PCString, fallback: &PCString) -> String {
+    let name = display_name(preferred);
+    if name.is_empty() {
+        display_name(fallback)
+    } else {
+        name
+    }
+}
+
+/// First unknown value only, a broken array would flood the report otherwise.
+fn check_enum<T: RawEnum>(found: &mut Vec<Anomaly>, field: &'static str, values: &[u32]) {
+    let unknown = values
+        .iter()
+        .find(|raw| T::checked(**raw).known().is_none());
+    if let Some(raw) = unknown {
+        found.push(Anomaly::UnknownEnum(field, *raw));
+    }
+}
+
+fn check_bool(found: &mut Vec<Anomaly>, field: &'static str, values: &[u8]) {
+    if let Some(raw) = values.iter().find(|raw| **raw > 1) {
+        found.push(Anomaly::InvalidBool(field, *raw));
+    }
+}
@@ -10,6 +10,16 @@ pub const TYRE_COMPOUND_NAME_LENGTH_MAX: usize = 40;
 pub const TYRE_MAX: usize = 4;
 pub const VEC_MAX: usize = 3;
 
+// sizeof() from SharedMemory9.h
+pub const SHARED_MEMORY_SIZE: usize = 20576;
+pub const PARTICIPANT_INFO_SIZE: usize = 100;
+
+// Fails to compile when structs below stop matching the header, tests at the bottom tell which field
+const _SHARED_MEMORY_SIZE_CHECK: [(); SHARED_MEMORY_SIZE] =
+  [(); std::mem::size_of::<SharedMemory>()];
+const _PARTICIPANT_INFO_SIZE_CHECK: [(); PARTICIPANT_INFO_SIZE] =
+  [(); std::mem::size_of::<ParticipantInfo>()];
+
 #[repr(u32)]
 #[derive(Debug, Clone)]
 pub enum Tyre {
@@ -22,7 +32,7 @@ pub enum Tyre {
 }
 
 #[repr(u32)]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameState {
   GAME_EXITED,
   GAME_FRONT_END,
@@ -37,7 +47,7 @@ pub enum GameState {
 }
 
 #[repr(u32)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SessionState {
   SESSION_INVALID,
   SESSION_PRACTICE,
@@ -52,7 +62,7 @@ pub enum SessionState {
 
 // (Type#3) RaceState (to be used with 'mRaceState' and 'mRaceStates')
 #[repr(u32)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RaceState {
   RACESTATE_INVALID,
   RACESTATE_NOT_STARTED,
@@ -67,7 +77,7 @@ pub enum RaceState {
 
 // (Type#5) Flag Colours (to be used with 'mHighestFlagColour')
 #[repr(u32)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FlagColour {
   FLAG_COLOUR_NONE = 0, // Not used for actual flags, only for some query functions
   FLAG_COLOUR_GREEN,    // End of danger zone, or race started
@@ -87,7 +97,7 @@ pub enum FlagColour {
 
 // (Type#6) Flag Reason (to be used with 'mHighestFlagReason')
 #[repr(u32)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FlagReason {
   FLAG_REASON_NONE = 0,
   FLAG_REASON_SOLO_CRASH,
@@ -99,7 +109,7 @@ pub enum FlagReason {
 
 // (Type#7) Pit Mode (to be used with 'mPitMode')
 #[repr(u32)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PitMode {
   PIT_MODE_NONE = 0,
   PIT_MODE_DRIVING_INTO_PITS,
@@ -114,7 +124,7 @@ pub enum PitMode {
 // (Type#8) Pit Stop Schedule (to be used with 'mPitSchedule')
 
 #[repr(u32)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PitSchedule {
   PIT_SCHEDULE_NONE = 0,           // Nothing scheduled
   PIT_SCHEDULE_PLAYER_REQUESTED,   // Used for standard pit sequence - requested by player
@@ -129,11 +139,15 @@ pub enum PitSchedule {
 }
 
 #[derive(Debug, Clone)]
+#[repr(C)]
 pub struct CarFlags {
   data: u32,
 }
 
 impl CarFlags {
+  pub fn new(data: u32) -> CarFlags {
+    CarFlags { data }
+  }
   pub fn headlight_on(&self) -> bool {
     self.data & (1 << 0) > 0
   }
@@ -168,6 +182,7 @@ impl CarFlags {
 // }
 
 #[derive(Debug, Clone)]
+#[repr(C)]
 pub struct TyreFlags {
   data: u32,
 }
@@ -248,7 +263,7 @@ pub enum TerrainType {
 
 // (Type#12) Crash Damage State  (to be used with 'mCrashState')
 #[repr(u32)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CrashDamageState {
   CRASH_DAMAGE_NONE = 0,
   CRASH_DAMAGE_OFFTRACK,
@@ -262,7 +277,7 @@ pub enum CrashDamageState {
 #[derive(Clone, Debug)]
 #[repr(C)]
 pub struct ParticipantInfo {
-  pub mIsActive: bool,
+  pub mIsActive: u8, // [ UNITS = boolean ]
   pub mName: StringArray<u8>,    // [ string ]
   pub mWorldPosition: Vec3<f32>, // [ UNITS = World Space  X  Y  Z ]
   pub mCurrentLapDistance: f32,  // [ UNITS = Metres ]   [ RANGE = 0.0f->... ]    [ UNSET = 0.0f ]
@@ -273,16 +288,19 @@ pub struct ParticipantInfo {
 }
 
 #[derive(Clone)]
+#[repr(C)]
 pub struct StringArray<T> {
   pub data: [T; STRING_LENGTH_MAX],
 }
 
 #[derive(Clone)]
+#[repr(C)]
 pub struct ParticipantsArray<T> {
   pub data: [T; STORED_PARTICIPANTS_MAX],
 }
 
 #[derive(Clone)]
+#[repr(C)]
 pub struct TyresArray<T> {
   pub data: [T; TYRE_MAX],
 }
@@ -298,6 +316,7 @@ impl TyresArray<f32> {
 }
 
 #[derive(Copy, Clone, Debug)]
+#[repr(C)]
 pub struct Vec3<T> {
   pub x: T,
   pub y: T,
@@ -319,14 +338,7 @@ impl PCString {
     }
   }
   pub fn to_string(&self) -> String {
-    let v = self
-      .data
-      .iter()
-      .map(|b| *b)
-      .take_while(|b| *b != 0u8)
-      .collect::<Vec<_>>();
-    let st = std::ffi::CString::new(v).unwrap_or(std::ffi::CString::new("").unwrap());
-    st.into_string().unwrap()
+    c_string(&self.data)
   }
 
   pub fn is_empty(&self) -> bool {
@@ -334,6 +346,41 @@ impl PCString {
   }
 }
 
+// Tyre compound names are shorter than the rest of strings
+#[derive(Clone)]
+#[repr(C)]
+pub struct TyreCompoundName {
+  pub data: [u8; TYRE_COMPOUND_NAME_LENGTH_MAX],
+}
+
+impl TyreCompoundName {
+  pub fn to_string(&self) -> String {
+    c_string(&self.data)
+  }
+}
+
+// Game writes UTF-8, but names typed by players can come as Latin-1, those are read byte by byte.
+// Never fails, a character cut by the length limit (no NUL found) is dropped.
+fn c_string(data: &[u8]) -> String {
+  let end = data.iter().position(|b| *b == 0u8).unwrap_or(data.len());
+  let bytes = &data[..end];
+
+  match std::str::from_utf8(bytes) {
+    Ok(st) => st.to_string(),
+    Err(ref e) if e.error_len().is_none() && end == data.len() => {
+      String::from_utf8_lossy(&bytes[..e.valid_up_to()]).into_owned()
+    }
+    Err(_) => bytes.iter().map(|b| *b as char).collect(),
+  }
+}
+
+impl fmt::Debug for TyreCompoundName {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    let st = self.to_string();
+    st.fmt(f)
+  }
+}
+
 impl fmt::Debug for PCString {
   fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
     let st = self.to_string();
@@ -353,6 +400,8 @@ impl<T: fmt::Debug> fmt::Debug for TyresArray<T> {
   }
 }
 
+// Enums and bools are kept as plain numbers, so any value the game writes can be read safely,
+// checked versions are in decode.rs
 #[derive(Clone, Debug)]
 #[repr(C)]
 pub struct SharedMemory {
@@ -360,9 +409,9 @@ pub struct SharedMemory {
   pub mVersion: u32,            // [ RANGE = 0->... ]
   pub mBuildVersionNumber: u32, // [ RANGE = 0->... ]   [ UNSET = 0 ]
   // Game States
-  pub mGameState: GameState,       // [ enum (Type#1) Game state ]
-  pub mSessionState: SessionState, // [ enum (Type#2) Session state ]
-  pub mRaceState: RaceState,       // [ enum (Type#3) Race State ]
+  pub mGameState: u32,    // [ enum (Type#1) Game state ]
+  pub mSessionState: u32, // [ enum (Type#2) Session state ]
+  pub mRaceState: u32,    // [ enum (Type#3) Race State ]
 
   // Participant Info
   pub mViewedParticipantIndex: i32, // [ RANGE = 0->STORED_PARTICIPANTS_MAX ]   [ UNSET = -1 ]
@@ -387,7 +436,7 @@ pub struct SharedMemory {
 
   // Timings
   pub mNumSectors: i32,                 // [ RANGE = 0->... ]   [ UNSET = -1 ]
-  pub mLapInvalidated: bool, // [ UNITS = boolean ]   [ RANGE = false->true ]   [ UNSET = false ]
+  pub mLapInvalidated: u8,   // [ UNITS = boolean ]   [ RANGE = false->true ]   [ UNSET = false ]
   pub mBestLapTime: f32,     // [ UNITS = seconds ]   [ RANGE = 0.0f->... ]   [ UNSET = -1.0f ]
   pub mLastLapTime: f32,     // [ UNITS = seconds ]   [ RANGE = 0.0f->... ]   [ UNSET = 0.0f ]
   pub mCurrentTime: f32,     // [ UNITS = seconds ]   [ RANGE = 0.0f->... ]   [ UNSET = 0.0f ]
@@ -415,8 +464,8 @@ pub struct SharedMemory {
   pub mHighestFlagReason: u32, // [ enum (Type#6) Flag Reason ]
 
   // Pit Info
-  pub mPitMode: PitMode,         // [ enum (Type#7) Pit Mode ]
-  pub mPitSchedule: PitSchedule, // [ enum (Type#8) Pit Stop Schedule ]
+  pub mPitMode: u32,     // [ enum (Type#7) Pit Mode ]
+  pub mPitSchedule: u32, // [ enum (Type#8) Pit Stop Schedule ]
 
   // Car State
   pub mCarFlags: CarFlags, // [ enum (Type#9) Car Flags ]
@@ -437,10 +486,10 @@ pub struct SharedMemory {
   pub mGear: i32,
   pub mNumGears: i32,
   pub mOdometerKM: f32,
-  pub mAntiLockActive: bool,
+  pub mAntiLockActive: u8, // [ UNITS = boolean ]
   pub mLastOpponentCollisionIndex: i32,
   pub mLastOpponentCollisionMagnitude: f32,
-  pub mBoostActive: bool,
+  pub mBoostActive: u8, // [ UNITS = boolean ]
   pub mBoostAmount: f32,
 
   pub mOrientation: Vec3<f32>,
@@ -470,7 +519,7 @@ pub struct SharedMemory {
   pub mTyreRimTemp: TyresArray<f32>,
   pub mTyreInternalAirTemp: TyresArray<f32>,
 
-  pub mCrashState: u32,
+  pub mCrashState: u32, // [ enum (Type#12) Crash Damage State ]
   pub mAeroDamage: f32,
   pub mEngineDamage: f32,
 
@@ -504,7 +553,7 @@ pub struct SharedMemory {
   pub mFastestSector3Times: ParticipantsArray<f32>,
   pub mFastestLapTimes: ParticipantsArray<f32>,
   pub mLastLapTimes: ParticipantsArray<f32>,
-  pub mLapsInvalidated: ParticipantsArray<bool>,
+  pub mLapsInvalidated: ParticipantsArray<u8>, // [ UNITS = boolean ]
   pub mRaceStates: ParticipantsArray<u32>,
   pub mPitModes: ParticipantsArray<u32>,
   pub mOrientations: ParticipantsArray<Vec3<f32>>, // Euler Angles
@@ -518,10 +567,237 @@ pub struct SharedMemory {
   pub mTranslatedTrackVariation: PCString,
   pub mBrakeBias: f32,          // [ RANGE = 0.0f->1.0f... ]   [ UNSET = -1.0f ]
   pub mTurboBoostPressure: f32, //	 RANGE = 0.0f->1.0f... ]   [ UNSET = -1.0f ]
-  pub mTyreCompound: TyresArray<PCString>, // [ strings  ]
+  pub mTyreCompound: TyresArray<TyreCompoundName>, // [ strings  ]
   pub mPitSchedules: ParticipantsArray<u32>, // [ enum (Type#7)  Pit Mode ]
   pub mHighestFlagColours: ParticipantsArray<u32>, // [ enum (Type#5) Flag Colour ]
   pub mHighestFlagReasons: ParticipantsArray<u32>, // [ enum (Type#6) Flag Reason ]
   pub mNationalities: ParticipantsArray<u32>, // [ nationality table , SP AND UNSET = 0 ] See nationalities.txt file for details
   pub mSnowDensity: f32, // [ UNITS = How much snow will fall ]   [ RANGE = 0.0f->1.0f ], this will be non zero only in Snow season, in other seasons whatever is falling from the sky is reported as rain
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::mem;
+
+  // Byte offset of every listed field, against offsetof() of SharedMemory9.h
+  macro_rules! assert_offsets {
+    ($value:expr, $($field:ident: $offset:expr,)+) => {
+      let base = &$value as *const _ as usize;
+      $(
+        assert_eq!(
+          &$value.$field as *const _ as usize - base,
+          $offset,
+          stringify!($field)
+        );
+      )+
+    };
+  }
+
+  #[test]
+  fn participant_info_layout() {
+    let info: ParticipantInfo = unsafe { mem::zeroed() };
+    assert_eq!(mem::size_of::<ParticipantInfo>(), PARTICIPANT_INFO_SIZE);
+    assert_offsets!(
+      info,
+      mIsActive: 0,
+      mName: 1,
+      mWorldPosition: 68,
+      mCurrentLapDistance: 80,
+      mRacePosition: 84,
+      mLapsCompleted: 88,
+      mCurrentLap: 92,
+      mCurrentSector: 96,
+    );
+  }
+
+  #[test]
+  fn arrays_layout() {
+    assert_eq!(mem::size_of::<PCString>(), STRING_LENGTH_MAX);
+    assert_eq!(mem::size_of::<TyreCompoundName>(), TYRE_COMPOUND_NAME_LENGTH_MAX);
+    assert_eq!(mem::size_of::<Vec3<f32>>(), 12);
+    assert_eq!(mem::size_of::<CarFlags>(), 4);
+    assert_eq!(mem::size_of::<TyresArray<f32>>(), 16);
+    assert_eq!(mem::size_of::<TyresArray<u32>>(), 16);
+    assert_eq!(mem::size_of::<TyresArray<TyreCompoundName>>(), 160);
+    assert_eq!(mem::size_of::<ParticipantsArray<u8>>(), 64);
+    assert_eq!(mem::size_of::<ParticipantsArray<f32>>(), 256);
+    assert_eq!(mem::size_of::<ParticipantsArray<Vec3<f32>>>(), 768);
+    assert_eq!(mem::size_of::<ParticipantsArray<PCString>>(), 4096);
+    assert_eq!(mem::size_of::<ParticipantsArray<ParticipantInfo>>(), 6400);
+  }
+
+  #[test]
+  fn shared_memory_layout() {
+    let sm: SharedMemory = unsafe { mem::zeroed() };
+    assert_eq!(mem::size_of::<SharedMemory>(), SHARED_MEMORY_SIZE);
+    assert_offsets!(
+      sm,
+      mVersion: 0,
+      mBuildVersionNumber: 4,
+      mGameState: 8,
+      mSessionState: 12,
+      mRaceState: 16,
+      mViewedParticipantIndex: 20,
+      mNumParticipants: 24,
+      mParticipantInfo: 28,
+      mUnfilteredThrottle: 6428,
+      mUnfilteredBrake: 6432,
+      mUnfilteredSteering: 6436,
+      mUnfilteredClutch: 6440,
+      mCarName: 6444,
+      mCarClassName: 6508,
+      mLapsInEvent: 6572,
+      mTrackLocation: 6576,
+      mTrackVariation: 6640,
+      mTrackLength: 6704,
+      mNumSectors: 6708,
+      mLapInvalidated: 6712,
+      mBestLapTime: 6716,
+      mLastLapTime: 6720,
+      mCurrentTime: 6724,
+      mSplitTimeAhead: 6728,
+      mSplitTimeBehind: 6732,
+      mSplitTime: 6736,
+      mEventTimeRemaining: 6740,
+      mPersonalFastestLapTime: 6744,
+      mWorldFastestLapTime: 6748,
+      mCurrentSector1Time: 6752,
+      mCurrentSector2Time: 6756,
+      mCurrentSector3Time: 6760,
+      mFastestSector1Time: 6764,
+      mFastestSector2Time: 6768,
+      mFastestSector3Time: 6772,
+      mPersonalFastestSector1Time: 6776,
+      mPersonalFastestSector2Time: 6780,
+      mPersonalFastestSector3Time: 6784,
+      mWorldFastestSector1Time: 6788,
+      mWorldFastestSector2Time: 6792,
+      mWorldFastestSector3Time: 6796,
+      mHighestFlagColour: 6800,
+      mHighestFlagReason: 6804,
+      mPitMode: 6808,
+      mPitSchedule: 6812,
+      mCarFlags: 6816,
+      mOilTempCelsius: 6820,
+      mOilPressureKPa: 6824,
+      mWaterTempCelsius: 6828,
+      mWaterPressureKPa: 6832,
+      mFuelPressureKPa: 6836,
+      mFuelLevel: 6840,
+      mFuelCapacity: 6844,
+      mSpeed: 6848,
+      mRpm: 6852,
+      mMaxRPM: 6856,
+      mBrake: 6860,
+      mThrottle: 6864,
+      mClutch: 6868,
+      mSteering: 6872,
+      mGear: 6876,
+      mNumGears: 6880,
+      mOdometerKM: 6884,
+      mAntiLockActive: 6888,
+      mLastOpponentCollisionIndex: 6892,
+      mLastOpponentCollisionMagnitude: 6896,
+      mBoostActive: 6900,
+      mBoostAmount: 6904,
+      mOrientation: 6908,
+      mLocalVelocity: 6920,
+      mWorldVelocity: 6932,
+      mAngularVelocity: 6944,
+      mLocalAcceleration: 6956,
+      mWorldAcceleration: 6968,
+      mExtentsCentre: 6980,
+      mTyreFlags: 6992,
+      mTerrain: 7008,
+      mTyreY: 7024,
+      mTyreRPS: 7040,
+      mTyreSlipSpeed: 7056,
+      mTyreTemp: 7072,
+      mTyreGrip: 7088,
+      mTyreHeightAboveGround: 7104,
+      mTyreLateralStiffness: 7120,
+      mTyreWear: 7136,
+      mBrakeDamage: 7152,
+      mSuspensionDamage: 7168,
+      mBrakeTempCelsius: 7184,
+      mTyreTreadTemp: 7200,
+      mTyreLayerTemp: 7216,
+      mTyreCarcassTemp: 7232,
+      mTyreRimTemp: 7248,
+      mTyreInternalAirTemp: 7264,
+      mCrashState: 7280,
+      mAeroDamage: 7284,
+      mEngineDamage: 7288,
+      mAmbientTemperature: 7292,
+      mTrackTemperature: 7296,
+      mRainDensity: 7300,
+      mWindSpeed: 7304,
+      mWindDirectionX: 7308,
+      mWindDirectionY: 7312,
+      mCloudBrightness: 7316,
+      mSequenceNumber: 7320,
+      mWheelLocalPositionY: 7324,
+      mSuspensionTravel: 7340,
+      mSuspensionVelocity: 7356,
+      mAirPressure: 7372,
+      mEngineSpeed: 7388,
+      mEngineTorque: 7392,
+      mWings: 7396,
+      mHandBrake: 7404,
+      mCurrentSector1Times: 7408,
+      mCurrentSector2Times: 7664,
+      mCurrentSector3Times: 7920,
+      mFastestSector1Times: 8176,
+      mFastestSector2Times: 8432,
+      mFastestSector3Times: 8688,
+      mFastestLapTimes: 8944,
+      mLastLapTimes: 9200,
+      mLapsInvalidated: 9456,
+      mRaceStates: 9520,
+      mPitModes: 9776,
+      mOrientations: 10032,
+      mSpeeds: 10800,
+      mCarNames: 11056,
+      mCarClassNames: 15152,
+      mEnforcedPitStopLap: 19248,
+      mTranslatedTrackLocation: 19252,
+      mTranslatedTrackVariation: 19316,
+      mBrakeBias: 19380,
+      mTurboBoostPressure: 19384,
+      mTyreCompound: 19388,
+      mPitSchedules: 19548,
+      mHighestFlagColours: 19804,
+      mHighestFlagReasons: 20060,
+      mNationalities: 20316,
+      mSnowDensity: 20572,
+    );
+  }
+
+  #[test]
+  fn c_string_utf8() {
+    assert_eq!(c_string("Andr\u{e9} Jos\u{e9}\0junk".as_bytes()), "Andr\u{e9} Jos\u{e9}");
+  }
+
+  #[test]
+  fn c_string_latin1() {
+    assert_eq!(c_string(b"Andr\xe9\0"), "Andr\u{e9}");
+    assert_eq!(c_string(b"Jos\xe9\0\0\0"), "Jos\u{e9}");
+    assert_eq!(c_string(b"M\xfcller Racing\0"), "M\u{fc}ller Racing");
+  }
+
+  #[test]
+  fn c_string_cut_mid_character() {
+    // "é" is 0xc3 0xa9, only its first byte fits
+    assert_eq!(c_string(b"Andr\xc3"), "Andr");
+  }
+
+  #[test]
+  fn c_string_without_nul() {
+    assert_eq!(c_string(b"Brands Hatch"), "Brands Hatch");
+    // whole buffer used, trailing lead byte is taken as a cut character
+    assert_eq!(c_string(b"Andr\xe9"), "Andr");
+    assert_eq!(c_string(b"Andr\xe9 "), "Andr\u{e9} ");
+    assert_eq!(c_string(b""), "");
+  }
+}
@@ -0,0 +1,300 @@
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use util::*;
+
+//km/h, corners below are mechanical grip, above - aero
+const LOW_SPEED: f32 = 120f32;
+const HIGH_SPEED: f32 = 180f32;
+//km/h per peak grip bucket
+const BUCKET: i32 = 20;
+const MAX_RUNS: usize = 8;
+
+/// Everything measured with one wing setting.
+pub struct AeroRun {
+    pub wings: [f32; 2],
+    grip: PeakGrip,
+    balance_low: Average,
+    balance_high: Average,
+    travel_low: [Average; 2],
+    travel_high: [Average; 2],
+}
+
+impl AeroRun {
+    fn new(wings: [f32; 2]) -> AeroRun {
+        AeroRun {
+            wings,
+            grip: PeakGrip::new(BUCKET),
+            balance_low: Average::default(),
+            balance_high: Average::default(),
+            travel_low: [Average::default(); 2],
+            travel_high: [Average::default(); 2],
+        }
+    }
+
+    /// Peak lateral g of every bucket against speed squared, downforce grows with it,
+    /// so intercept is mechanical grip and slope is aero.
+    pub fn grip_fit(&self) -> LinearFit {
+        let mut fit = LinearFit::new();
+        for (bucket, peak) in self.grip.peaks() {
+            let speed = (bucket + BUCKET / 2) as f32 / 100f32;
+            fit.add(speed * speed, peak);
+        }
+        fit
+    }
+
+    pub fn grip_at(&self, speed: f32) -> Option<f32> {
+        let fit = self.grip_fit();
+        if fit.samples() < 3 {
+            return None;
+        }
+        let speed = speed / 100f32;
+        match (fit.slope(), fit.intercept()) {
+            (Some(slope), Some(intercept)) => Some(intercept + slope * speed * speed),
+            _ => None,
+        }
+    }
+
+    /// How much more understeer there is in fast corners, negative means the rear gets lighter.
+    pub fn balance_shift(&self) -> Option<f32> {
+        match (self.balance_low.value(), self.balance_high.value()) {
+            (Some(low), Some(high)) => Some(high - low),
+            _ => None,
+        }
+    }
+
+    /// Extra suspension travel on fast straights, front and rear, mm.
+    pub fn compression(&self, axle: usize) -> Option<f32> {
+        match (
+            self.travel_low[axle].value(),
+            self.travel_high[axle].value(),
+        ) {
+            (Some(low), Some(high)) => Some((high - low) * 1000f32),
+            _ => None,
+        }
+    }
+}
+
+pub struct AeroGraphData {
+    runs: Vec<AeroRun>,
+}
+
+impl AeroGraphData {
+    pub fn new() -> AeroGraphData {
+        AeroGraphData { runs: Vec::new() }
+    }
+
+    fn run_mut(&mut self, wings: [f32; 2]) -> &mut AeroRun {
+        let same = |a: f32, b: f32| (a - b).abs() < 0.005;
+        let found = self
+            .runs
+            .iter()
+            .position(|r| same(r.wings[0], wings[0]) && same(r.wings[1], wings[1]));
+
+        let ix = match found {
+            Some(ix) => ix,
+            None => {
+                if self.runs.len() >= MAX_RUNS {
+                    self.runs.remove(0);
+                }
+                self.runs.push(AeroRun::new(wings));
+                self.runs.len() - 1
+            }
+        };
+
+        &mut self.runs[ix]
+    }
+
+    /// Accelerations in g, positive longtitudal is braking, speed in km/h,
+    /// front and rear suspension travel in meters.
+    pub fn add(
+        &mut self,
+        wings: [f32; 2],
+        speed: f32,
+        lateral: f32,
+        longtitudal: f32,
+        travel: (f32, f32),
+        understeer: Option<f32>,
+    ) {
+        let run = self.run_mut(wings);
+        let lateral = lateral.abs();
+
+        //steady cornering only, braking and traction shift the load
+        if lateral > 0.5 && longtitudal.abs() < 0.3 {
+            run.grip.add(speed as i32, lateral);
+
+            if let Some(understeer) = understeer {
+                if speed < LOW_SPEED {
+                    run.balance_low.add(understeer);
+                } else if speed > HIGH_SPEED {
+                    run.balance_high.add(understeer);
+                }
+            }
+        }
+
+        //straights for ride height
+        if lateral < 0.2 && longtitudal.abs() < 0.2 {
+            let (front, rear) = travel;
+            if speed < LOW_SPEED {
+                run.travel_low[0].add(front);
+                run.travel_low[1].add(rear);
+            } else if speed > HIGH_SPEED {
+                run.travel_high[0].add(front);
+                run.travel_high[1].add(rear);
+            }
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+        current_wings: [f32; 2],
+    ) -> GameResult<()> {
+        let grid = (1, 2);
+        let line_height = 0.06;
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(
+            ctx,
+            "Wings F/R   grip @100   grip @200   aero gain   balance slow/fast   shift   compression F/R",
+            scale_left_cell(0f32, 1.1, (0, 0), grid, screen_size),
+        )?;
+
+        let format_opt = |value: Option<f32>, precision: usize| -> String {
+            value
+                .map(|v| format!("{:.*}", precision, v))
+                .unwrap_or_else(|| "-".to_string())
+        };
+
+        let colors = run_colors();
+
+        for (n, run) in self.runs.iter().enumerate() {
+            let current = (run.wings[0] - current_wings[0]).abs() < 0.005
+                && (run.wings[1] - current_wings[1]).abs() < 0.005;
+
+            let gain = match (run.grip_at(100f32), run.grip_at(200f32)) {
+                (Some(low), Some(high)) => Some(high - low),
+                _ => None,
+            };
+
+            let line = format!(
+                "{:.2}/{:.2}{}   {}g   {}g   {}g   {}/{}   {}   {}/{} mm",
+                run.wings[0],
+                run.wings[1],
+                if current { "*" } else { " " },
+                format_opt(run.grip_at(100f32), 2),
+                format_opt(run.grip_at(200f32), 2),
+                format_opt(gain, 2),
+                format_opt(run.balance_low.value(), 2),
+                format_opt(run.balance_high.value(), 2),
+                format_opt(run.balance_shift(), 2),
+                format_opt(run.compression(0), 1),
+                format_opt(run.compression(1), 1),
+            );
+
+            graphics::set_color(ctx, colors[n % colors.len()])?;
+            let y = 1f32 - line_height * (n + 1) as f32;
+            labels.draw(
+                ctx,
+                &line,
+                scale_left_cell(0f32, y, (0, 0), grid, screen_size),
+            )?;
+        }
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(
+            ctx,
+            "balance: 0 neutral, above understeer, below oversteer; negative shift - rear lighter at speed",
+            scale_left_cell(0f32, 0f32, (0, 0), grid, screen_size),
+        )?;
+
+        //fitted lateral grip against speed, one line per wing setting
+        let max_speed = 300f32;
+        let max_g = 4f32;
+
+        graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+        for g in 1..(max_g as i32) {
+            let y = g as f32 / max_g;
+            graphics::line(
+                ctx,
+                &[
+                    scale_left_cell(0f32, y, (0, 1), grid, screen_size),
+                    scale_left_cell(1f32, y, (0, 1), grid, screen_size),
+                ],
+                1f32,
+            )?;
+        }
+        for speed in (50..(max_speed as i32)).step_by(50) {
+            let x = speed as f32 / max_speed;
+            graphics::line(
+                ctx,
+                &[
+                    scale_left_cell(x, 0f32, (0, 1), grid, screen_size),
+                    scale_left_cell(x, 1f32, (0, 1), grid, screen_size),
+                ],
+                1f32,
+            )?;
+        }
+
+        for (n, run) in self.runs.iter().enumerate() {
+            let points: Vec<Point2> = (0..31)
+                .filter_map(|step| {
+                    let speed = step as f32 * 10f32;
+                    run.grip_at(speed).map(|g| {
+                        scale_left_cell(
+                            speed / max_speed,
+                            (g / max_g).max(0f32).min(1f32),
+                            (0, 1),
+                            grid,
+                            screen_size,
+                        )
+                    })
+                })
+                .collect();
+
+            let peaks: Vec<Point2> = run
+                .grip
+                .peaks()
+                .into_iter()
+                .map(|(bucket, peak)| {
+                    scale_left_cell(
+                        (bucket + BUCKET / 2) as f32 / max_speed,
+                        (peak / max_g).min(1f32),
+                        (0, 1),
+                        grid,
+                        screen_size,
+                    )
+                })
+                .collect();
+
+            graphics::set_color(ctx, colors[n % colors.len()])?;
+            if points.len() > 1 {
+                graphics::line(ctx, &points, 2f32)?;
+            }
+            for peak in peaks.iter() {
+                graphics::circle(ctx, DrawMode::Fill, *peak, 3f32, 1f32)?;
+            }
+        }
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(
+            ctx,
+            "95th percentile lateral g per 20 km/h and fit against speed squared, 1g lines, 50 km/h steps",
+            scale_left_cell(0f32, 1.1, (0, 1), grid, screen_size),
+        )?;
+
+        Ok(())
+    }
+}
+
+fn run_colors() -> [Color; 4] {
+    [
+        Color::from_rgb(86, 226, 86),
+        Color::from_rgb(0, 191, 255),
+        Color::from_rgb(255, 201, 14),
+        Color::from_rgb(177, 100, 255),
+    ]
+}
@@ -0,0 +1,414 @@
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use std::collections::VecDeque;
+use std::mem;
+use util::*;
+
+//m/s, below that yaw rate is mostly noise
+const MIN_SPEED: f32 = 10f32;
+//rad/s
+const MIN_YAW_RATE: f32 = 0.05;
+const BASELINE_SMOOTHING: f32 = 0.002;
+
+/// Curvature of the path car follows, 1/m. Steering geometry needs wheelbase times that,
+/// game doesn't give wheelbase and it is the same for every sample of a car,
+/// so it is left out rather than estimated.
+pub fn path_curvature(yaw_rate: f32, speed: f32) -> f32 {
+    yaw_rate / speed
+}
+
+/// Steering input against path curvature, relative to the same ratio in gentle corners,
+/// so neither steering lock nor wheelbase has to be known.
+/// Zero is neutral, positive is understeer, negative is oversteer, below -1 is countersteer.
+pub struct UndersteerEstimator {
+    baseline: Option<f32>,
+    baseline_samples: u32,
+}
+
+impl UndersteerEstimator {
+    pub fn new() -> UndersteerEstimator {
+        UndersteerEstimator {
+            baseline: None,
+            baseline_samples: 0,
+        }
+    }
+
+    pub fn index(&mut self, steering: f32, yaw_rate: f32, speed: f32, lateral: f32) -> Option<f32> {
+        if speed < MIN_SPEED || yaw_rate.abs() < MIN_YAW_RATE {
+            return None;
+        }
+
+        let ratio = steering / path_curvature(yaw_rate, speed);
+
+        //linear range of the tyres, well below the limit
+        if lateral.abs() > 0.2 && lateral.abs() < 0.6 {
+            let baseline = self.baseline.unwrap_or(ratio);
+            self.baseline = Some(baseline + (ratio - baseline) * BASELINE_SMOOTHING);
+            self.baseline_samples += 1;
+        }
+
+        match self.baseline {
+            Some(baseline) if self.baseline_samples > 100 && baseline.abs() > 1e-3 => {
+                Some(ratio / baseline - 1f32)
+            }
+            _ => None,
+        }
+    }
+}
+
+//g, corner starts above ENTER and ends below EXIT
+const CORNER_ENTER: f32 = 0.4;
+const CORNER_EXIT: f32 = 0.25;
+//meters, shorter ones are kinks or kerb hits
+const CORNER_MIN_LENGTH: f32 = 20f32;
+const SAMPLE_STEP: f32 = 2f32;
+const LATERAL_BIN: f32 = 0.1;
+const LATERAL_BINS: usize = 30;
+const SCATTER_MAX: usize = 3000;
+//clamps for plotting
+const INDEX_RANGE: f32 = 1f32;
+
+const PHASE_NAMES: [&str; 3] = ["entry", "mid", "exit"];
+
+/// Same corner over all laps, matched by distance.
+pub struct Corner {
+    pub start: f32,
+    pub end: f32,
+    /// Lap averages of every phase, one value per lap.
+    pub phases: [Average; 3],
+    pub last: [Option<f32>; 3],
+}
+
+impl Corner {
+    fn overlaps(&self, start: f32, end: f32) -> bool {
+        start < self.end && end > self.start
+    }
+}
+
+pub struct BalanceGraphData {
+    current: Vec<(f32, f32)>,
+    last_lap: Vec<(f32, f32)>,
+    current_lap: i32,
+    by_lateral: Vec<Average>,
+    scatter: VecDeque<(f32, f32)>,
+    corners: Vec<Corner>,
+    active: Option<Vec<(f32, f32)>>,
+}
+
+impl BalanceGraphData {
+    pub fn new() -> BalanceGraphData {
+        BalanceGraphData {
+            current: Vec::new(),
+            last_lap: Vec::new(),
+            current_lap: 0,
+            by_lateral: vec![Average::default(); LATERAL_BINS],
+            scatter: VecDeque::new(),
+            corners: Vec::new(),
+            active: None,
+        }
+    }
+
+    /// Lateral in g, index as given by `UndersteerEstimator`.
+    pub fn add(&mut self, lap: i32, distance: f32, lateral: f32, index: Option<f32>) {
+        if lap != self.current_lap {
+            self.finish_corner();
+            self.last_lap = mem::replace(&mut self.current, Vec::new());
+            self.current_lap = lap;
+        }
+
+        let lateral = lateral.abs();
+
+        if lateral < CORNER_EXIT {
+            self.finish_corner();
+        } else if lateral > CORNER_ENTER && self.active.is_none() {
+            self.active = Some(Vec::new());
+        }
+
+        let index = match index {
+            Some(index) if distance >= 0f32 => index,
+            _ => return,
+        };
+
+        if let Some(ref mut samples) = self.active {
+            samples.push((distance, index));
+        }
+
+        let bin = ((lateral / LATERAL_BIN) as usize).min(LATERAL_BINS - 1);
+        self.by_lateral[bin].add(index);
+
+        if self.scatter.len() >= SCATTER_MAX {
+            self.scatter.pop_front();
+        }
+        self.scatter.push_back((lateral, index));
+
+        let far_enough = self
+            .current
+            .last()
+            .map(|s| distance - s.0 >= SAMPLE_STEP)
+            .unwrap_or(true);
+        if far_enough {
+            self.current.push((distance, index));
+        }
+    }
+
+    fn finish_corner(&mut self) {
+        let samples = match self.active.take() {
+            Some(samples) => samples,
+            None => return,
+        };
+
+        let (start, end) = match (samples.first(), samples.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return,
+        };
+
+        if end - start < CORNER_MIN_LENGTH {
+            return;
+        }
+
+        //thirds by distance
+        let mut phases = [Average::default(); 3];
+        for &(distance, index) in samples.iter() {
+            let phase = (((distance - start) / (end - start) * 3f32) as usize).min(2);
+            phases[phase].add(index);
+        }
+        let last = [phases[0].value(), phases[1].value(), phases[2].value()];
+
+        let ix = match self.corners.iter().position(|c| c.overlaps(start, end)) {
+            Some(ix) => ix,
+            None => {
+                self.corners.push(Corner {
+                    start,
+                    end,
+                    phases: [Average::default(); 3],
+                    last,
+                });
+                self.corners
+                    .sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+                self.corners
+                    .iter()
+                    .position(|c| c.start == start)
+                    .unwrap_or(0)
+            }
+        };
+
+        let corner = &mut self.corners[ix];
+        for (average, value) in corner.phases.iter_mut().zip(last.iter()) {
+            if let Some(value) = *value {
+                average.add(value);
+            }
+        }
+        corner.last = last;
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+        track_length: f32,
+    ) -> GameResult<()> {
+        let grid = (2, 2);
+        let plot_y = |index: f32| (index / INDEX_RANGE / 2f32 + 0.5).max(0f32).min(1f32);
+
+        //by distance, whole top row
+        {
+            let wide = (1, 2);
+            let to_screen = |distance: f32, index: f32| -> Point2 {
+                scale_left_cell(
+                    distance / track_length,
+                    plot_y(index),
+                    (0, 0),
+                    wide,
+                    screen_size,
+                )
+            };
+
+            graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+            graphics::line(
+                ctx,
+                &[to_screen(0f32, 0f32), to_screen(track_length, 0f32)],
+                1f32,
+            )?;
+
+            for (n, corner) in self.corners.iter().enumerate() {
+                graphics::set_color(ctx, Color::from_rgba(255, 255, 255, 30))?;
+                let top_left = to_screen(corner.start, INDEX_RANGE);
+                let bottom_right = to_screen(corner.end, -INDEX_RANGE);
+                graphics::rectangle(
+                    ctx,
+                    DrawMode::Fill,
+                    Rect::new(
+                        top_left.x,
+                        top_left.y,
+                        bottom_right.x - top_left.x,
+                        bottom_right.y - top_left.y,
+                    ),
+                )?;
+                graphics::set_color(ctx, WHITE)?;
+                labels.draw(
+                    ctx,
+                    &format!("C{}", n + 1),
+                    to_screen(corner.start, INDEX_RANGE),
+                )?;
+            }
+
+            draw_trace(
+                ctx,
+                &self.last_lap,
+                Color::from_rgba(0, 191, 255, 90),
+                &to_screen,
+            )?;
+            draw_trace(ctx, &self.current, Color::from_rgb(0, 191, 255), &to_screen)?;
+
+            graphics::set_color(ctx, WHITE)?;
+            labels.draw(
+                ctx,
+                "Understeer index by distance, above zero understeer, below oversteer, faded: last lap",
+                scale_left_cell(0f32, 1.1, (0, 0), wide, screen_size),
+            )?;
+        }
+
+        //against lateral g
+        {
+            let max_g = LATERAL_BIN * LATERAL_BINS as f32;
+            let to_screen = |lateral: f32, index: f32| -> Point2 {
+                scale_left_cell(lateral / max_g, plot_y(index), (0, 1), grid, screen_size)
+            };
+
+            graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+            graphics::line(ctx, &[to_screen(0f32, 0f32), to_screen(max_g, 0f32)], 1f32)?;
+            for g in 1..(max_g as i32) {
+                graphics::line(
+                    ctx,
+                    &[
+                        to_screen(g as f32, -INDEX_RANGE),
+                        to_screen(g as f32, INDEX_RANGE),
+                    ],
+                    1f32,
+                )?;
+            }
+
+            let points: Vec<Point2> = self
+                .scatter
+                .iter()
+                .map(|&(lateral, index)| to_screen(lateral, index))
+                .collect();
+            if !points.is_empty() {
+                graphics::set_color(ctx, Color::from_rgba(0, 191, 255, 60))?;
+                graphics::points(ctx, &points, 2f32)?;
+            }
+
+            let averages: Vec<Point2> = self
+                .by_lateral
+                .iter()
+                .enumerate()
+                .filter_map(|(bin, average)| {
+                    average
+                        .value()
+                        .map(|index| to_screen((bin as f32 + 0.5) * LATERAL_BIN, index))
+                })
+                .collect();
+            if averages.len() > 1 {
+                graphics::set_color(ctx, Color::from_rgb(255, 201, 14))?;
+                graphics::line(ctx, &averages, 2f32)?;
+            }
+
+            graphics::set_color(ctx, WHITE)?;
+            labels.draw(
+                ctx,
+                "Index against lateral g, 1g lines, yellow: average",
+                scale_left_cell(0f32, 1.1, (0, 1), grid, screen_size),
+            )?;
+        }
+
+        //per corner table
+        {
+            let line_height = 1f32 / 16f32;
+            //rows under the header that still end above bottom of the cell
+            let rows = (1f32 / line_height) as usize - 1;
+            graphics::set_color(ctx, WHITE)?;
+            labels.draw(
+                ctx,
+                &format!(
+                    "Corner   at      {} / {} / {}, average (last lap)",
+                    PHASE_NAMES[0], PHASE_NAMES[1], PHASE_NAMES[2]
+                ),
+                scale_left_cell(0f32, 1.1, (1, 1), grid, screen_size),
+            )?;
+
+            let format_opt = |value: Option<f32>| -> String {
+                value
+                    .map(|v| format!("{:+.2}", v))
+                    .unwrap_or_else(|| "-".to_string())
+            };
+
+            for (n, corner) in self.corners.iter().take(rows).enumerate() {
+                let phases: Vec<String> = (0..3)
+                    .map(|phase| {
+                        format!(
+                            "{} ({})",
+                            format_opt(corner.phases[phase].mean()),
+                            format_opt(corner.last[phase])
+                        )
+                    })
+                    .collect();
+
+                let worst = corner
+                    .phases
+                    .iter()
+                    .filter_map(|p| p.mean())
+                    .fold(0f32, |acc, v| if v.abs() > acc.abs() { v } else { acc });
+
+                let color = if worst > 0.15 {
+                    Color::from_rgb(255, 201, 14)
+                } else if worst < -0.15 {
+                    Color::from_rgb(250, 0, 0)
+                } else {
+                    WHITE
+                };
+
+                graphics::set_color(ctx, color)?;
+                labels.draw(
+                    ctx,
+                    &format!(
+                        "C{}   {}m   {}",
+                        n + 1,
+                        corner.start as i32,
+                        phases.join(" / ")
+                    ),
+                    scale_left_cell(
+                        0f32,
+                        1f32 - line_height * (n + 1) as f32,
+                        (1, 1),
+                        grid,
+                        screen_size,
+                    ),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn draw_trace<F>(
+    ctx: &mut Context,
+    samples: &[(f32, f32)],
+    color: Color,
+    to_screen: &F,
+) -> GameResult<()>
+where
+    F: Fn(f32, f32) -> Point2,
+{
+    if samples.len() < 2 {
+        return Ok(());
+    }
+
+    let points: Vec<Point2> = samples.iter().map(|&(d, i)| to_screen(d, i)).collect();
+    graphics::set_color(ctx, color)?;
+    graphics::line(ctx, &points, 2f32)?;
+    Ok(())
+}
@@ -0,0 +1,422 @@
+use decode::*;
+use definitions::*;
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use super::*;
+use std::mem;
+use std::time::Duration;
+use util::*;
+
+//seconds without new damage before a collision is considered over
+const EVENT_SETTLE: u64 = 3;
+//share of damage, smaller changes are noise
+const DAMAGE_STEP: f32 = 0.005;
+const EVENTS_MAX: usize = 50;
+
+const CRASH_NAMES: [&str; 5] = ["none", "off track", "large prop", "spinning", "rolling"];
+const TYRE_NAMES: [&str; 4] = ["FL", "FR", "RL", "RR"];
+
+/// Damage of every component, 0 to 1.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DamageLevels {
+    pub aero: f32,
+    pub engine: f32,
+    pub brakes: [f32; 4],
+    pub suspension: [f32; 4],
+}
+
+impl DamageLevels {
+    pub fn from(sm: &SharedMemory) -> DamageLevels {
+        DamageLevels {
+            aero: sm.mAeroDamage,
+            engine: sm.mEngineDamage,
+            brakes: sm.mBrakeDamage.data,
+            suspension: sm.mSuspensionDamage.data,
+        }
+    }
+
+    fn values(&self) -> [f32; 10] {
+        let b = self.brakes;
+        let s = self.suspension;
+        [
+            self.aero,
+            self.engine,
+            b[0],
+            b[1],
+            b[2],
+            b[3],
+            s[0],
+            s[1],
+            s[2],
+            s[3],
+        ]
+    }
+
+    /// Largest increase of a single component against earlier levels.
+    pub fn increase_over(&self, before: &DamageLevels) -> f32 {
+        self.values()
+            .iter()
+            .zip(before.values().iter())
+            .map(|(after, before)| after - before)
+            .fold(0f32, f32::max)
+    }
+
+    /// Names of components that got worse, with new levels.
+    pub fn describe_increase(&self, before: &DamageLevels) -> String {
+        let names = [
+            "aero", "engine", "brake FL", "brake FR", "brake RL", "brake RR", "susp FL", "susp FR",
+            "susp RL", "susp RR",
+        ];
+
+        let parts: Vec<String> = self
+            .values()
+            .iter()
+            .zip(before.values().iter())
+            .zip(names.iter())
+            .filter(|((after, before), _)| *after - *before > DAMAGE_STEP)
+            .map(|((after, before), name)| {
+                format!(
+                    "{} {}->{}%",
+                    name,
+                    (before * 100f32) as i32,
+                    (after * 100f32) as i32
+                )
+            })
+            .collect();
+
+        if parts.is_empty() {
+            "no damage".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DamageEvent {
+    pub lap: i32,
+    pub distance: f32,
+    pub crash_state: u32,
+    pub opponent: Option<String>,
+    pub magnitude: f32,
+    pub before: DamageLevels,
+    pub after: DamageLevels,
+    last_change: Duration,
+}
+
+/// Grip and power curves, collected between collisions.
+pub struct DamageCurves {
+    pub grip: GripBySpeed,
+    pub power: GraphLine,
+    pub max_rpm: f32,
+    pub max_power: f32,
+}
+
+impl DamageCurves {
+    fn new(rpm_step: i32) -> DamageCurves {
+        DamageCurves {
+            grip: GripBySpeed::new(),
+            power: GraphLine::new(rpm_step, false, false, GraphRegion::Left, 3),
+            max_rpm: 1f32,
+            max_power: 1f32,
+        }
+    }
+}
+
+pub struct DamageMonitor {
+    rpm_step: i32,
+    levels: Option<DamageLevels>,
+    crash_state: u32,
+    collision: (i32, f32),
+    open: Option<DamageEvent>,
+    pub events: VecDeque<DamageEvent>,
+    pub curves: DamageCurves,
+    pub curves_before: Option<DamageCurves>,
+    pub show_power: bool,
+}
+
+impl DamageMonitor {
+    pub fn new(rpm_step: i32) -> DamageMonitor {
+        DamageMonitor {
+            rpm_step,
+            levels: None,
+            crash_state: 0,
+            collision: (-1, 0f32),
+            open: None,
+            events: VecDeque::new(),
+            curves: DamageCurves::new(rpm_step),
+            curves_before: None,
+            show_power: false,
+        }
+    }
+
+    pub fn add(&mut self, sm: &SharedMemory, time: Duration) {
+        let levels = DamageLevels::from(sm);
+        let crash_state = sm.mCrashState;
+        let collision = (
+            sm.mLastOpponentCollisionIndex,
+            sm.mLastOpponentCollisionMagnitude,
+        );
+
+        //car may come with damage already, nothing to compare against yet
+        let previous = match self.levels {
+            Some(previous) => previous,
+            None => {
+                self.levels = Some(levels);
+                self.crash_state = crash_state;
+                self.collision = collision;
+                return;
+            }
+        };
+
+        let new_crash = crash_state != 0 && crash_state != self.crash_state;
+        let new_collision = collision.0 >= 0 && collision != self.collision;
+        let new_damage = levels.increase_over(&previous) > DAMAGE_STEP;
+
+        let opponent = if new_collision {
+            let ix = collision.0 as usize;
+            if collision.0 < sm.mNumParticipants && ix < sm.mParticipantInfo.data.len() {
+                Some(display_name(&sm.mParticipantInfo.data[ix].mName))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        if new_crash || new_collision || new_damage {
+            if self.open.is_none() {
+                let viewed = sm.viewed_participant().unwrap_or(0);
+                let participant = &sm.mParticipantInfo.data[viewed];
+                self.open = Some(DamageEvent {
+                    lap: participant.mCurrentLap,
+                    distance: participant.mCurrentLapDistance,
+                    crash_state: 0,
+                    opponent: None,
+                    magnitude: 0f32,
+                    before: previous,
+                    after: levels,
+                    last_change: time,
+                });
+            }
+
+            if let Some(ref mut event) = self.open {
+                event.last_change = time;
+                event.crash_state = event.crash_state.max(crash_state);
+                if new_collision {
+                    event.magnitude = event.magnitude.max(collision.1);
+                }
+                if opponent.is_some() {
+                    event.opponent = opponent;
+                }
+            }
+        }
+
+        let settled = match self.open {
+            Some(ref mut event) => {
+                //damage model catches up over a few frames
+                event.after = levels;
+                time.checked_sub(event.last_change).unwrap_or_default()
+                    > Duration::from_secs(EVENT_SETTLE)
+            }
+            None => false,
+        };
+
+        if settled {
+            self.close();
+        }
+
+        self.levels = Some(levels);
+        self.crash_state = crash_state;
+        self.collision = collision;
+    }
+
+    fn close(&mut self) {
+        let event = match self.open.take() {
+            Some(event) => event,
+            None => return,
+        };
+
+        //curves so far are "before", start collecting "after" from scratch
+        if event.after.increase_over(&event.before) > DAMAGE_STEP {
+            let curves = mem::replace(&mut self.curves, DamageCurves::new(self.rpm_step));
+            self.curves_before = Some(curves);
+        }
+
+        if self.events.len() >= EVENTS_MAX {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Accelerations in g, positive longtitudal is braking, speed in km/h.
+    pub fn add_grip(
+        &mut self,
+        lateral: f32,
+        longtitudal: f32,
+        speed: f32,
+        wings: [f32; 2],
+        crash_state: u32,
+    ) {
+        if crash_state == 0 && self.open.is_none() {
+            self.curves.grip.add(lateral, longtitudal, speed, wings);
+        }
+    }
+
+    pub fn add_power(&mut self, rpm: i32, power: f32, current_only: bool) {
+        if self.open.is_none() {
+            let curves = &mut self.curves;
+            curves.power.add(rpm, power, current_only);
+            if !current_only {
+                curves.max_rpm = curves.max_rpm.max(rpm as f32);
+                curves.max_power = curves.max_power.max(power);
+            }
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+    ) -> GameResult<()> {
+        let line_height = 0.025;
+        let mut y = 1f32;
+
+        let percent = |v: f32| (v * 100f32) as i32;
+        let levels = self.levels.unwrap_or_default();
+        let summary = [
+            format!(
+                "Aero {}%   Engine {}%",
+                percent(levels.aero),
+                percent(levels.engine)
+            ),
+            format!(
+                "Brakes {}",
+                (0..4)
+                    .map(|i| format!("{} {}%", TYRE_NAMES[i], percent(levels.brakes[i])))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            ),
+            format!(
+                "Suspension {}",
+                (0..4)
+                    .map(|i| format!("{} {}%", TYRE_NAMES[i], percent(levels.suspension[i])))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            ),
+        ];
+
+        let worst = levels.values().iter().cloned().fold(0f32, f32::max);
+        graphics::set_color(ctx, damage_color(worst))?;
+        for line in summary.iter() {
+            labels.draw(ctx, line, scale_left(0.01, y, screen_size))?;
+            y -= line_height;
+        }
+
+        y -= line_height;
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(
+            ctx,
+            "Collisions and damage, newest first",
+            scale_left(0.01, y, screen_size),
+        )?;
+
+        for event in self.open.iter().chain(self.events.iter().rev()).take(14) {
+            y -= line_height;
+
+            let who = match event.opponent {
+                Some(ref name) => format!(" with {} ({:.1})", name, event.magnitude),
+                None => String::new(),
+            };
+            let crash = CRASH_NAMES
+                .get(event.crash_state as usize)
+                .cloned()
+                .unwrap_or("unknown");
+
+            let line = format!(
+                "Lap {} at {}m, {}{}: {}",
+                event.lap,
+                event.distance as i32,
+                crash,
+                who,
+                event.after.describe_increase(&event.before)
+            );
+
+            graphics::set_color(ctx, damage_color(event.after.increase_over(&event.before)))?;
+            labels.draw(ctx, &line, scale_left(0.01, y, screen_size))?;
+        }
+
+        //curves take bottom half of the panel, so scale everything twice as high
+        if self.show_power {
+            let max_rpm = self
+                .curves_before
+                .as_ref()
+                .map(|c| c.max_rpm)
+                .unwrap_or(0f32)
+                .max(self.curves.max_rpm);
+            let max_power = self
+                .curves_before
+                .as_ref()
+                .map(|c| c.max_power)
+                .unwrap_or(0f32)
+                .max(self.curves.max_power);
+            let max_values = Point2::new(max_rpm, max_power * 2.2);
+
+            let hp_color = Color::from_rgb(197, 67, 67);
+            if let Some(ref mut before) = self.curves_before {
+                let mut faded = hp_color;
+                faded.a = 0.35;
+                before
+                    .power
+                    .draw(ctx, faded, faded, screen_size, &max_values)?;
+            }
+            self.curves
+                .power
+                .draw(ctx, hp_color, hp_color, screen_size, &max_values)?;
+        } else {
+            let max_speed = self
+                .curves_before
+                .as_ref()
+                .map(|c| c.grip.max_speed)
+                .unwrap_or(0f32)
+                .max(self.curves.grip.max_speed);
+            let max_values = Point2::new(max_speed, 8f32);
+
+            if let Some(ref mut before) = self.curves_before {
+                before.grip.draw(ctx, screen_size, &max_values, true)?;
+            }
+            self.curves
+                .grip
+                .draw(ctx, screen_size, &max_values, false)?;
+        }
+
+        let legend = if self.show_power {
+            "Power vs rpm at full throttle"
+        } else {
+            "Grip vs speed: cornering (purple), braking (red), traction (green)"
+        };
+        let legend = if self.curves_before.is_some() {
+            format!("{}, faded: before last damage", legend)
+        } else {
+            format!("{}, no damage yet", legend)
+        };
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(ctx, &legend, scale_left(0.01, 0.5, screen_size))?;
+
+        Ok(())
+    }
+}
+
+fn damage_color(damage: f32) -> Color {
+    if damage > 0.25 {
+        Color::from_rgb(250, 0, 0)
+    } else if damage > DAMAGE_STEP {
+        Color::from_rgb(255, 201, 14)
+    } else {
+        WHITE
+    }
+}
@@ -0,0 +1,237 @@
+use definitions::*;
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use std::mem;
+use util::*;
+
+//mm/s, same buckets setup sheets use
+const BIN_WIDTH: f32 = 25f32;
+const BIN_COUNT: usize = 24;
+//where low speed ends and high speed begins, mm/s
+const KNEE: f32 = 50f32;
+
+const CORNERS: [(Tyre, &str, (usize, usize)); 4] = [
+    (Tyre::TyreFrontLeft, "FL", (0, 0)),
+    (Tyre::TyreFrontRight, "FR", (1, 0)),
+    (Tyre::TyreRearLeft, "RL", (0, 1)),
+    (Tyre::TyreRearRight, "RR", (1, 1)),
+];
+
+#[derive(Clone)]
+pub struct DamperHistogram {
+    bins: [u32; BIN_COUNT],
+    //low bump, high bump, low rebound, high rebound
+    zones: [u32; 4],
+    samples: u32,
+}
+
+impl DamperHistogram {
+    pub fn new() -> DamperHistogram {
+        DamperHistogram {
+            bins: [0; BIN_COUNT],
+            zones: [0; 4],
+            samples: 0,
+        }
+    }
+
+    //positive velocity is bump, negative is rebound
+    pub fn add(&mut self, velocity: f32) {
+        let mm_s = velocity * 1000f32;
+        let half = (BIN_COUNT / 2) as f32;
+        let bin = ((mm_s / BIN_WIDTH).floor() + half)
+            .max(0f32)
+            .min(BIN_COUNT as f32 - 1f32) as usize;
+
+        let zone = match (mm_s >= 0f32, mm_s.abs() < KNEE) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (false, false) => 3,
+        };
+
+        self.bins[bin] += 1;
+        self.zones[zone] += 1;
+        self.samples += 1;
+    }
+
+    pub fn share(&self, bin: usize) -> f32 {
+        if self.samples == 0 {
+            0f32
+        } else {
+            self.bins[bin] as f32 / self.samples as f32
+        }
+    }
+
+    pub fn zone_percent(&self, zone: usize) -> i32 {
+        if self.samples == 0 {
+            0
+        } else {
+            (self.zones[zone] as f32 * 100f32 / self.samples as f32).round() as i32
+        }
+    }
+
+    pub fn max_share(&self) -> f32 {
+        (0..BIN_COUNT).map(|b| self.share(b)).fold(0f32, f32::max)
+    }
+}
+
+pub struct DamperGraphData {
+    session: Vec<DamperHistogram>,
+    lap: Vec<DamperHistogram>,
+    last_lap: Option<Vec<DamperHistogram>>,
+    reference: Option<Vec<DamperHistogram>>,
+    current_lap: i32,
+    last_travel: Option<[f32; TYRE_MAX]>,
+}
+
+impl DamperGraphData {
+    pub fn new() -> DamperGraphData {
+        DamperGraphData {
+            session: vec![DamperHistogram::new(); TYRE_MAX],
+            lap: vec![DamperHistogram::new(); TYRE_MAX],
+            last_lap: None,
+            reference: None,
+            current_lap: 0,
+            last_travel: None,
+        }
+    }
+
+    //header doesn't say which way velocity is signed, so speed is taken from it
+    //and direction from travel, which grows in bump
+    pub fn add(&mut self, travel: &TyresArray<f32>, velocity: &TyresArray<f32>, lap: i32) {
+        if lap != self.current_lap {
+            if self.lap.iter().any(|h| h.samples > 0) {
+                let finished = mem::replace(&mut self.lap, vec![DamperHistogram::new(); TYRE_MAX]);
+                self.last_lap = Some(finished);
+            }
+            self.current_lap = lap;
+        }
+
+        let last_travel = match mem::replace(&mut self.last_travel, Some(travel.data)) {
+            Some(last_travel) => last_travel,
+            None => return,
+        };
+
+        for (ix, v) in velocity.data.iter().enumerate() {
+            let v = if travel.data[ix] >= last_travel[ix] {
+                v.abs()
+            } else {
+                -v.abs()
+            };
+            self.session[ix].add(v);
+            self.lap[ix].add(v);
+        }
+    }
+
+    //pins the session histogram, so the next setup can be compared against it.
+    pub fn pin_reference(&mut self) {
+        self.reference = Some(self.session.clone());
+        self.session = vec![DamperHistogram::new(); TYRE_MAX];
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+        numeric_cache: &NumericTextCache,
+    ) -> GameResult<()> {
+        //session since pinning against reference, otherwise this lap against last one
+        let (shown, compare) = match self.reference {
+            Some(ref reference) => (&self.session, Some(reference)),
+            None => (&self.lap, self.last_lap.as_ref()),
+        };
+
+        for (tyre, name, cell) in CORNERS.iter() {
+            let ix = tyre.clone() as usize;
+            let current = &shown[ix];
+            let other = compare.map(|c| &c[ix]);
+
+            let max_share = current
+                .max_share()
+                .max(other.map(|o| o.max_share()).unwrap_or(0f32))
+                .max(0.01);
+
+            let grid = (2, 2);
+            let bar_width = 1f32 / BIN_COUNT as f32;
+
+            for bin in 0..BIN_COUNT {
+                let share = current.share(bin) / max_share;
+                if share <= 0f32 {
+                    continue;
+                }
+
+                let top_left =
+                    scale_left_cell(bin as f32 * bar_width, share, *cell, grid, screen_size);
+                let bottom_right =
+                    scale_left_cell((bin + 1) as f32 * bar_width, 0f32, *cell, grid, screen_size);
+
+                let color = if bin < BIN_COUNT / 2 {
+                    Color::from_rgb(0, 191, 255)
+                } else {
+                    Color::from_rgb(236, 87, 15)
+                };
+
+                graphics::set_color(ctx, color)?;
+                graphics::rectangle(
+                    ctx,
+                    DrawMode::Fill,
+                    Rect::new(
+                        top_left.x + 1f32,
+                        top_left.y,
+                        bottom_right.x - top_left.x - 2f32,
+                        bottom_right.y - top_left.y,
+                    ),
+                )?;
+            }
+
+            if let Some(other) = other {
+                let points: Vec<Point2> = (0..BIN_COUNT)
+                    .map(|bin| {
+                        scale_left_cell(
+                            (bin as f32 + 0.5) * bar_width,
+                            other.share(bin) / max_share,
+                            *cell,
+                            grid,
+                            screen_size,
+                        )
+                    })
+                    .collect();
+
+                graphics::set_color(ctx, WHITE)?;
+                graphics::line(ctx, &points, 1f32)?;
+            }
+
+            //zero velocity
+            graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+            graphics::line(
+                ctx,
+                &[
+                    scale_left_cell(0.5, 0f32, *cell, grid, screen_size),
+                    scale_left_cell(0.5, 1f32, *cell, grid, screen_size),
+                ],
+                1f32,
+            )?;
+
+            graphics::set_color(ctx, WHITE)?;
+            let mut dest = scale_left_cell(0f32, 1.1, *cell, grid, screen_size);
+            dest.x += labels.draw(ctx, name, dest)? + 10f32;
+
+            for (zone, label) in ["LS bump", "HS bump", "LS reb", "HS reb"]
+                .iter()
+                .enumerate()
+            {
+                dest.x += labels.draw(ctx, &format!("{}: ", label), dest)?;
+                if let Some(value) = numeric_cache.small_numbers.get(&current.zone_percent(zone)) {
+                    graphics::draw(ctx, value, dest, 0f32)?;
+                    dest.x += value.width() as f32;
+                }
+                dest.x += labels.draw(ctx, "%", dest)? + 8f32;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use cars::*;
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use super::*;
+use util::*;
+
+const ROWS: usize = 32;
+//cars of same class listed below details
+const CLASS_ROWS: usize = 12;
+
+/// Peak power measured on full throttle against published figure, or class average.
+pub struct PowerCheck {
+    pub measured: f32,
+    pub rpm: i32,
+    pub published: Option<f32>,
+    pub class_average: Option<f32>,
+}
+
+impl PowerCheck {
+    pub fn from(
+        car: Option<&CarInfo>,
+        class: Option<&ClassDefaults>,
+        power: &PowerGraphData,
+    ) -> Option<PowerCheck> {
+        power.power.peak().map(|(rpm, measured)| PowerCheck {
+            measured,
+            rpm,
+            published: car.and_then(|c| c.power_hp),
+            class_average: class.and_then(|c| c.power_hp),
+        })
+    }
+
+    /// Power compared against and what it is.
+    pub fn reference(&self) -> Option<(f32, &'static str)> {
+        match (self.published, self.class_average) {
+            (Some(p), _) if p > 0f32 => Some((p, "published")),
+            (_, Some(p)) if p > 0f32 => Some((p, "class average")),
+            _ => None,
+        }
+    }
+
+    /// Share measured is above reference, negative when below.
+    pub fn difference(&self) -> Option<f32> {
+        self.reference().map(|(p, _)| self.measured / p - 1f32)
+    }
+}
+
+pub fn draw_car_details(
+    ctx: &mut Context,
+    screen_size: &Point2,
+    labels: &mut TextCache,
+    cars: &AllCarsData,
+    power: &PowerGraphData,
+) -> GameResult<()> {
+    let line_height = 1f32 / (ROWS + 2) as f32;
+    let car = cars.current();
+    let class = cars.current_class();
+
+    graphics::set_color(ctx, WHITE)?;
+    let mut rows = match (car, class) {
+        (Some(car), _) => car_rows(car, class),
+        (None, Some(class)) => vec![
+            ("Car", "unknown, class defaults".to_string()),
+            ("Class", class.name.clone()),
+        ],
+        (None, None) => {
+            labels.draw(ctx, "No car details", scale_left(0.01, 1f32, screen_size))?;
+            return Ok(());
+        }
+    };
+
+    if let Some(class) = class {
+        rows.push((
+            "Class FFB",
+            format!(
+                "SD: {}/{}, REG: {}/{} ({} cars)",
+                class.sd_vol,
+                class.sd_tone,
+                class.norm_vol,
+                class.norm_tone,
+                class.cars.len()
+            ),
+        ));
+        rows.push((
+            "Class power",
+            or_unknown(class.power_hp.map(|p| match class.power_to_weight() {
+                Some(ratio) => format!("{:.0} hp, {:.0} hp/t", p, ratio),
+                None => format!("{:.0} hp", p),
+            })),
+        ));
+    }
+
+    for (n, (title, value)) in rows.iter().enumerate() {
+        let y = 1f32 - line_height * n as f32;
+        labels.draw(ctx, title, scale_left(0.01, y, screen_size))?;
+        labels.draw(ctx, value, scale_left(0.3, y, screen_size))?;
+    }
+
+    //measured against published
+    let y = 1f32 - line_height * (rows.len() + 1) as f32;
+    labels.draw(ctx, "Measured peak", scale_left(0.01, y, screen_size))?;
+    let text = match PowerCheck::from(car, class, power) {
+        Some(check) => match (check.difference(), check.reference()) {
+            (Some(difference), Some((_, against))) => {
+                let off = Color::from_rgb(250, 0, 0);
+                let close = Color::from_rgb(86, 226, 86);
+                graphics::set_color(ctx, if difference.abs() > 0.1 { off } else { close })?;
+                format!(
+                    "{:.0} hp @ {} rpm, {:+.1}% to {}",
+                    check.measured,
+                    check.rpm,
+                    difference * 100f32,
+                    against
+                )
+            }
+            _ => format!("{:.0} hp @ {} rpm", check.measured, check.rpm),
+        },
+        None => "full throttle in gear to measure".to_string(),
+    };
+    labels.draw(ctx, &text, scale_left(0.3, y, screen_size))?;
+
+    //rest of the class
+    if let Some(class) = class {
+        graphics::set_color(ctx, WHITE)?;
+        let top = rows.len() + 3;
+        labels.draw(
+            ctx,
+            &format!("{} cars", class.name),
+            scale_left(0.01, 1f32 - line_height * top as f32, screen_size),
+        )?;
+        for (n, other) in cars.class_cars(class).iter().take(CLASS_ROWS).enumerate() {
+            let y = 1f32 - line_height * (top + n + 1) as f32;
+            labels.draw(ctx, &other.full_name(), scale_left(0.01, y, screen_size))?;
+            labels.draw(
+                ctx,
+                &format!(
+                    "SD: {}/{}, REG: {}/{}",
+                    other.sd_vol, other.sd_tone, other.norm_vol, other.norm_tone
+                ),
+                scale_left(0.3, y, screen_size),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn car_rows(car: &CarInfo, class: Option<&ClassDefaults>) -> Vec<(&'static str, String)> {
+    let class_name = car.class.clone().or(class.map(|c| c.name.clone()));
+
+    vec![
+        ("Car", car.full_name()),
+        ("Class", or_unknown(class_name)),
+        (
+            "Drivetrain",
+            or_unknown(car.drivetrain.map(|d| d.name().to_string())),
+        ),
+        (
+            "Engine",
+            or_unknown(car.engine_layout.map(|e| e.name().to_string())),
+        ),
+        (
+            "Steering ratio",
+            or_unknown(car.steering_ratio.map(|r| format!("{}:1", r))),
+        ),
+        (
+            "Caster",
+            or_unknown(car.caster.map(|c| format!("{:.1} deg", c))),
+        ),
+        (
+            "Power",
+            or_unknown(car.power_hp.map(|p| format!("{:.0} hp", p))),
+        ),
+        (
+            "Weight",
+            or_unknown(car.weight_kg.map(|w| format!("{:.0} kg", w))),
+        ),
+        (
+            "Power to weight",
+            or_unknown(car.power_to_weight().map(|p| format!("{:.0} hp/t", p))),
+        ),
+        (
+            "FFB",
+            format!(
+                "SD: {}/{}, REG: {}/{}",
+                car.sd_vol, car.sd_tone, car.norm_vol, car.norm_tone
+            ),
+        ),
+        ("Notes", or_unknown(car.notes.clone())),
+    ]
+}
+
+fn or_unknown(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "-".to_string())
+}
@@ -0,0 +1,420 @@
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use std::collections::VecDeque;
+use std::time::Duration;
+use util::*;
+
+//seconds
+const HISTORY: u64 = 600;
+const TREND_SAMPLES: usize = 30;
+//warn this long before the trend crosses a limit, seconds
+const TREND_WARNING: f32 = 60f32;
+
+pub const GAUGES: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitKind {
+    Above,
+    Below,
+}
+
+/// Plot range, alarm threshold and how much one key press moves it.
+#[derive(Debug, Clone, Copy)]
+pub struct Gauge {
+    pub name: &'static str,
+    pub unit: &'static str,
+    pub min: f32,
+    pub max: f32,
+    pub limit: f32,
+    pub kind: LimitKind,
+    pub step: f32,
+    /// Off for channels many cars don't simulate, on once a threshold is set by hand.
+    pub monitored: bool,
+}
+
+impl Gauge {
+    fn alarm(&self, value: f32) -> bool {
+        if !self.monitored {
+            return false;
+        }
+        match self.kind {
+            LimitKind::Above => value > self.limit,
+            LimitKind::Below => value < self.limit,
+        }
+    }
+}
+
+/// Pressures only make sense with engine running, temperatures and damage are always checked.
+const PRESSURES: [bool; GAUGES] = [false, true, false, true, true, false];
+
+fn default_gauges() -> [Gauge; GAUGES] {
+    [
+        Gauge {
+            name: "Oil temp",
+            unit: "C",
+            min: 40f32,
+            max: 160f32,
+            limit: 135f32,
+            kind: LimitKind::Above,
+            step: 1f32,
+            monitored: true,
+        },
+        Gauge {
+            name: "Oil pressure",
+            unit: "kPa",
+            min: 0f32,
+            max: 800f32,
+            limit: 100f32,
+            kind: LimitKind::Below,
+            step: 10f32,
+            monitored: true,
+        },
+        Gauge {
+            name: "Water temp",
+            unit: "C",
+            min: 40f32,
+            max: 140f32,
+            limit: 110f32,
+            kind: LimitKind::Above,
+            step: 1f32,
+            monitored: true,
+        },
+        Gauge {
+            name: "Water pressure",
+            unit: "kPa",
+            min: 0f32,
+            max: 300f32,
+            limit: 50f32,
+            kind: LimitKind::Below,
+            step: 5f32,
+            monitored: false,
+        },
+        Gauge {
+            name: "Fuel pressure",
+            unit: "kPa",
+            min: 0f32,
+            max: 800f32,
+            limit: 150f32,
+            kind: LimitKind::Below,
+            step: 10f32,
+            monitored: false,
+        },
+        Gauge {
+            name: "Engine damage",
+            unit: "%",
+            min: 0f32,
+            max: 100f32,
+            limit: 5f32,
+            kind: LimitKind::Above,
+            step: 1f32,
+            monitored: true,
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alert {
+    Alarm,
+    Trend(f32),
+}
+
+pub struct EngineHealthData {
+    pub gauges: [Gauge; GAUGES],
+    pub selected: usize,
+    history: VecDeque<(Duration, [f32; GAUGES])>,
+    current: [f32; GAUGES],
+    current_time: Duration,
+    engine_running: bool,
+    pub engine_warning: bool,
+}
+
+impl EngineHealthData {
+    pub fn new() -> EngineHealthData {
+        EngineHealthData {
+            gauges: default_gauges(),
+            selected: 0,
+            history: VecDeque::new(),
+            current: [0f32; GAUGES],
+            current_time: Duration::from_secs(0),
+            engine_running: false,
+            engine_warning: false,
+        }
+    }
+
+    /// Values in the order of gauges, damage as 0..1 as game gives it.
+    pub fn add(
+        &mut self,
+        values: [f32; GAUGES],
+        engine_running: bool,
+        engine_warning: bool,
+        time: Duration,
+    ) {
+        let mut values = values;
+        values[5] *= 100f32;
+
+        self.current = values;
+        self.current_time = time;
+        self.engine_running = engine_running;
+        self.engine_warning = engine_warning;
+
+        let due = self
+            .history
+            .back()
+            .map(|(t, _)| time.checked_sub(*t).unwrap_or_default() >= Duration::from_secs(1))
+            .unwrap_or(true);
+
+        if due {
+            self.history.push_back((time, values));
+        }
+
+        if let Some(start) = time.checked_sub(Duration::from_secs(HISTORY)) {
+            while self
+                .history
+                .front()
+                .map(|(t, _)| *t < start)
+                .unwrap_or(false)
+            {
+                self.history.pop_front();
+            }
+        }
+    }
+
+    pub fn select(&mut self, up: bool) {
+        self.selected = if up {
+            (self.selected + GAUGES - 1) % GAUGES
+        } else {
+            (self.selected + 1) % GAUGES
+        };
+    }
+
+    pub fn adjust(&mut self, steps: f32) {
+        let gauge = &mut self.gauges[self.selected];
+        //first press only turns monitoring on, at the suggested limit
+        if gauge.monitored {
+            gauge.limit = (gauge.limit + gauge.step * steps)
+                .max(gauge.min)
+                .min(gauge.max);
+        }
+        gauge.monitored = true;
+    }
+
+    fn checked(&self, gauge: usize) -> bool {
+        self.gauges[gauge].monitored && (self.engine_running || !PRESSURES[gauge])
+    }
+
+    /// Seconds until recent trend crosses the limit, if it is heading there at all.
+    fn time_to_limit(&self, gauge: usize) -> Option<f32> {
+        if self.history.len() < TREND_SAMPLES {
+            return None;
+        }
+
+        let first = self.history[self.history.len() - TREND_SAMPLES].0;
+        let mut fit = LinearFit::new();
+        for (time, values) in self.history.iter().skip(self.history.len() - TREND_SAMPLES) {
+            let seconds = time.checked_sub(first).unwrap_or_default();
+            let seconds = seconds.as_secs() as f32 + seconds.subsec_millis() as f32 / 1000f32;
+            fit.add(seconds, values[gauge]);
+        }
+
+        let slope = fit.slope()?;
+        let g = &self.gauges[gauge];
+        let heading = match g.kind {
+            LimitKind::Above => slope > 0f32,
+            LimitKind::Below => slope < 0f32,
+        };
+
+        if heading {
+            Some((g.limit - self.current[gauge]) / slope)
+        } else {
+            None
+        }
+    }
+
+    pub fn alert(&self, gauge: usize) -> Option<Alert> {
+        if !self.checked(gauge) {
+            return None;
+        }
+
+        if self.gauges[gauge].alarm(self.current[gauge]) {
+            Some(Alert::Alarm)
+        } else {
+            match self.time_to_limit(gauge) {
+                Some(seconds) if seconds > 0f32 && seconds < TREND_WARNING => {
+                    Some(Alert::Trend(seconds))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    /// Banner on top of any panel, so it is seen without switching to engine view.
+    pub fn draw_alert(
+        &self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+    ) -> GameResult<()> {
+        let mut messages = Vec::new();
+        let mut alarm = self.engine_warning;
+
+        if self.engine_warning {
+            messages.push("ENGINE WARNING".to_string());
+        }
+
+        for ix in 0..GAUGES {
+            let gauge = &self.gauges[ix];
+            match self.alert(ix) {
+                Some(Alert::Alarm) => {
+                    alarm = true;
+                    messages.push(format!(
+                        "{} {:.0}{}",
+                        gauge.name.to_uppercase(),
+                        self.current[ix],
+                        gauge.unit
+                    ));
+                }
+                Some(Alert::Trend(seconds)) => {
+                    messages.push(format!("{} limit in {:.0}s", gauge.name, seconds))
+                }
+                None => {}
+            }
+        }
+
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let color = if alarm {
+            Color::from_rgb(250, 0, 0)
+        } else {
+            Color::from_rgb(255, 201, 14)
+        };
+
+        graphics::set_color(ctx, color)?;
+        labels.draw(
+            ctx,
+            &messages.join("   "),
+            //below max hp / rpm line and other banners
+            Point2::new(screen_size.x * 0.01, screen_size.y * 0.12),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+    ) -> GameResult<()> {
+        let grid = (2, 3);
+
+        for ix in 0..GAUGES {
+            let gauge = &self.gauges[ix];
+            let cell = (ix % 2, ix / 2);
+            let to_screen = |time: Duration, value: f32| -> Point2 {
+                let age = self.current_time.checked_sub(time).unwrap_or_default();
+                let x = 1f32 - age.as_secs() as f32 / HISTORY as f32;
+                let y = ((value - gauge.min) / (gauge.max - gauge.min))
+                    .max(0f32)
+                    .min(1f32);
+                scale_left_cell(x, y, cell, grid, screen_size)
+            };
+
+            graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+            graphics::line(
+                ctx,
+                &[
+                    to_screen(self.current_time, gauge.min),
+                    to_screen(self.current_time, gauge.max),
+                ],
+                1f32,
+            )?;
+
+            let alert = self.alert(ix);
+            let color = match alert {
+                Some(Alert::Alarm) => Color::from_rgb(250, 0, 0),
+                Some(Alert::Trend(_)) => Color::from_rgb(255, 201, 14),
+                None => Color::from_rgb(0, 191, 255),
+            };
+
+            let points: Vec<Point2> = self
+                .history
+                .iter()
+                .map(|(time, values)| to_screen(*time, values[ix]))
+                .collect();
+            if points.len() > 1 {
+                graphics::set_color(ctx, color)?;
+                graphics::line(ctx, &points, 2f32)?;
+            }
+
+            let oldest = self
+                .current_time
+                .checked_sub(Duration::from_secs(HISTORY))
+                .unwrap_or_default();
+            if gauge.monitored {
+                graphics::set_color(ctx, Color::from_rgba(250, 0, 0, 160))?;
+                graphics::line(
+                    ctx,
+                    &[
+                        to_screen(oldest, gauge.limit),
+                        to_screen(self.current_time, gauge.limit),
+                    ],
+                    1f32,
+                )?;
+            }
+
+            let status = if !gauge.monitored {
+                "not monitored, left/right to set alarm".to_string()
+            } else if !self.checked(ix) {
+                "engine off".to_string()
+            } else {
+                match alert {
+                    Some(Alert::Alarm) => "ALARM".to_string(),
+                    Some(Alert::Trend(seconds)) => format!("limit in {:.0}s", seconds),
+                    None => String::new(),
+                }
+            };
+
+            let limit = if gauge.monitored {
+                format!(
+                    ", alarm {} {:.0}{}",
+                    match gauge.kind {
+                        LimitKind::Above => "above",
+                        LimitKind::Below => "below",
+                    },
+                    gauge.limit,
+                    gauge.unit
+                )
+            } else {
+                String::new()
+            };
+            let title = format!(
+                "{}{}: {:.0}{}{}  {}",
+                if ix == self.selected { "> " } else { "" },
+                gauge.name,
+                self.current[ix],
+                gauge.unit,
+                limit,
+                status
+            );
+
+            graphics::set_color(ctx, WHITE)?;
+            labels.draw(
+                ctx,
+                &title,
+                scale_left_cell(0f32, 1.1, cell, grid, screen_size),
+            )?;
+        }
+
+        labels.draw(
+            ctx,
+            "Last 10 minutes, up/down: pick gauge, left/right: move alarm threshold",
+            scale_left(0.01, 0.02, screen_size),
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use std::collections::BTreeMap;
+use std::f32;
+use util::*;
+
+//g, both axes
+const MAX_G: f32 = 3f32;
+const DENSITY_CELLS: usize = 61;
+const DENSITY_LEVELS: usize = 5;
+const SECTORS: usize = 36;
+const RADIUS_BIN: f32 = 0.05;
+const RADIUS_BINS: usize = 80;
+//share of samples in a sector that stay inside the envelope, cuts off kerb spikes
+const ENVELOPE_PERCENTILE: f32 = 0.98;
+
+//km/h, upper bounds
+pub const SPEED_BANDS: [f32; 4] = [80f32, 140f32, 200f32, f32::MAX];
+const SPEED_BAND_NAMES: [&str; 4] = ["<80 km/h", "80-140", "140-200", ">200"];
+
+pub struct Envelope {
+    bins: Vec<[u32; RADIUS_BINS]>,
+    counts: [u32; SECTORS],
+}
+
+impl Envelope {
+    pub fn new() -> Envelope {
+        Envelope {
+            bins: vec![[0; RADIUS_BINS]; SECTORS],
+            counts: [0; SECTORS],
+        }
+    }
+
+    pub fn add(&mut self, lateral: f32, longtitudal: f32) {
+        let radius = (lateral.powi(2) + longtitudal.powi(2)).sqrt();
+        let sector = sector_of(lateral, longtitudal);
+        let bin = ((radius / RADIUS_BIN) as usize).min(RADIUS_BINS - 1);
+
+        self.bins[sector][bin] += 1;
+        self.counts[sector] += 1;
+    }
+
+    pub fn radius(&self, sector: usize) -> Option<f32> {
+        let count = self.counts[sector];
+        if count < 20 {
+            return None;
+        }
+
+        let limit = (count as f32 * ENVELOPE_PERCENTILE) as u32;
+        let mut seen = 0;
+        for (bin, n) in self.bins[sector].iter().enumerate() {
+            seen += n;
+            if seen >= limit {
+                return Some((bin + 1) as f32 * RADIUS_BIN);
+            }
+        }
+        None
+    }
+
+    /// Highest percentile radius over all sectors, so a single number for the whole circle.
+    pub fn max_radius(&self) -> Option<f32> {
+        (0..SECTORS)
+            .filter_map(|s| self.radius(s))
+            .fold(None, |acc: Option<f32>, r| {
+                Some(acc.map(|a| a.max(r)).unwrap_or(r))
+            })
+    }
+}
+
+fn sector_of(lateral: f32, longtitudal: f32) -> usize {
+    let angle = longtitudal.atan2(lateral) + f32::consts::PI;
+    ((angle / (2f32 * f32::consts::PI) * SECTORS as f32) as usize).min(SECTORS - 1)
+}
+
+fn sector_angle(sector: usize) -> f32 {
+    (sector as f32 + 0.5) / SECTORS as f32 * 2f32 * f32::consts::PI - f32::consts::PI
+}
+
+pub fn speed_band(speed_kmh: f32) -> usize {
+    SPEED_BANDS
+        .iter()
+        .position(|limit| speed_kmh < *limit)
+        .unwrap_or(SPEED_BANDS.len() - 1)
+}
+
+pub struct FrictionCircleData {
+    density: Vec<u32>,
+    density_max: u32,
+    density_cache: Option<Vec<(usize, Mesh)>>,
+    since_cache: u32,
+    speed_envelopes: Vec<Envelope>,
+    gear_envelopes: BTreeMap<i32, Envelope>,
+}
+
+impl FrictionCircleData {
+    pub fn new() -> FrictionCircleData {
+        FrictionCircleData {
+            density: vec![0; DENSITY_CELLS * DENSITY_CELLS],
+            density_max: 1,
+            density_cache: None,
+            since_cache: 0,
+            speed_envelopes: (0..SPEED_BANDS.len()).map(|_| Envelope::new()).collect(),
+            gear_envelopes: BTreeMap::new(),
+        }
+    }
+
+    /// Accelerations in g, positive longtitudal is braking, speed in m/s.
+    pub fn add(&mut self, lateral: f32, longtitudal: f32, speed: f32, gear: i32) {
+        if lateral.abs() >= MAX_G || longtitudal.abs() >= MAX_G {
+            return;
+        }
+
+        let cell = |g: f32| -> usize {
+            (((g / MAX_G + 1f32) / 2f32 * DENSITY_CELLS as f32) as usize).min(DENSITY_CELLS - 1)
+        };
+        //acceleration up, braking down
+        let ix = cell(-longtitudal) * DENSITY_CELLS + cell(lateral);
+        self.density[ix] += 1;
+        self.density_max = self.density_max.max(self.density[ix]);
+
+        self.since_cache += 1;
+        if self.since_cache > 60 {
+            self.density_cache = None;
+        }
+
+        self.speed_envelopes[speed_band(speed * 3.6)].add(lateral, -longtitudal);
+
+        if gear > 0 {
+            self.gear_envelopes
+                .entry(gear)
+                .or_insert_with(Envelope::new)
+                .add(lateral, -longtitudal);
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+    ) -> GameResult<()> {
+        let left = CircleArea::new((0, 0), screen_size);
+        let right = CircleArea::new((1, 0), screen_size);
+
+        left.draw_axes(ctx)?;
+        right.draw_axes(ctx)?;
+
+        if self.density_cache.is_none() {
+            let meshes = self.build_density(ctx, &left)?;
+            self.density_cache = Some(meshes);
+            self.since_cache = 0;
+        }
+
+        if let Some(ref meshes) = self.density_cache {
+            for (level, mesh) in meshes.iter() {
+                let alpha = (level + 1) as f32 / DENSITY_LEVELS as f32 * 0.8;
+                graphics::set_color(ctx, Color::new(0.0, 0.75, 1.0, alpha))?;
+                graphics::draw(ctx, mesh, Point2::new(0f32, 0f32), 0f32)?;
+            }
+        }
+
+        let band_colors = [
+            Color::from_rgb(86, 226, 86),
+            Color::from_rgb(255, 201, 14),
+            Color::from_rgb(236, 87, 15),
+            Color::from_rgb(250, 0, 0),
+        ];
+
+        let mut legend = left.label_origin();
+        for (band, envelope) in self.speed_envelopes.iter().enumerate() {
+            graphics::set_color(ctx, band_colors[band])?;
+            left.draw_envelope(ctx, envelope)?;
+            legend.x += labels.draw(ctx, SPEED_BAND_NAMES[band], legend)? + 8f32;
+        }
+
+        let max_gear = self.gear_envelopes.keys().last().cloned().unwrap_or(1);
+        let mut legend = right.label_origin();
+        for (gear, envelope) in self.gear_envelopes.iter() {
+            let mut color = WHITE;
+            color.a = *gear as f32 / max_gear as f32;
+            graphics::set_color(ctx, color)?;
+            right.draw_envelope(ctx, envelope)?;
+
+            let radius = envelope
+                .max_radius()
+                .map(|r| format!("{}: {:.2}g", gear, r))
+                .unwrap_or_else(|| format!("{}: -", gear));
+            legend.x += labels.draw(ctx, &radius, legend)? + 8f32;
+        }
+
+        Ok(())
+    }
+
+    fn build_density(
+        &self,
+        ctx: &mut Context,
+        area: &CircleArea,
+    ) -> GameResult<Vec<(usize, Mesh)>> {
+        let mut builders: Vec<MeshBuilder> =
+            (0..DENSITY_LEVELS).map(|_| MeshBuilder::new()).collect();
+        let mut used = [false; DENSITY_LEVELS];
+        let log_max = (1f32 + self.density_max as f32).ln();
+        let step = 2f32 * MAX_G / DENSITY_CELLS as f32;
+
+        for (ix, count) in self.density.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+
+            let level = ((1f32 + *count as f32).ln() / log_max * DENSITY_LEVELS as f32) as usize;
+            let level = level.min(DENSITY_LEVELS - 1);
+
+            let x = (ix % DENSITY_CELLS) as f32 * step - MAX_G;
+            let y = (ix / DENSITY_CELLS) as f32 * step - MAX_G;
+
+            let corners = [
+                area.to_screen(x, y),
+                area.to_screen(x + step, y),
+                area.to_screen(x + step, y + step),
+                area.to_screen(x, y + step),
+            ];
+
+            builders[level].polygon(DrawMode::Fill, &corners);
+            used[level] = true;
+        }
+
+        let mut meshes = Vec::new();
+        for (level, builder) in builders.iter().enumerate() {
+            if used[level] {
+                meshes.push((level, builder.build(ctx)?));
+            }
+        }
+        Ok(meshes)
+    }
+}
+
+/// Square plot inside a left panel cell, 1g per ring.
+struct CircleArea {
+    center: Point2,
+    scale: f32,
+}
+
+impl CircleArea {
+    fn new(cell: (usize, usize), screen_size: &Point2) -> CircleArea {
+        let grid = (2, 1);
+        let bottom_left = scale_left_cell(0f32, 0f32, cell, grid, screen_size);
+        let top_right = scale_left_cell(1f32, 1f32, cell, grid, screen_size);
+
+        let width = top_right.x - bottom_left.x;
+        let height = bottom_left.y - top_right.y;
+
+        CircleArea {
+            center: Point2::new(
+                (bottom_left.x + top_right.x) / 2f32,
+                (bottom_left.y + top_right.y) / 2f32,
+            ),
+            scale: width.min(height) / 2f32 / MAX_G,
+        }
+    }
+
+    /// Lateral to the right, acceleration up.
+    fn to_screen(&self, lateral: f32, longtitudal: f32) -> Point2 {
+        Point2::new(
+            self.center.x + lateral * self.scale,
+            self.center.y - longtitudal * self.scale,
+        )
+    }
+
+    fn label_origin(&self) -> Point2 {
+        let mut point = self.to_screen(-MAX_G, MAX_G);
+        point.y -= 14f32;
+        point
+    }
+
+    fn draw_axes(&self, ctx: &mut Context) -> GameResult<()> {
+        graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+        graphics::line(
+            ctx,
+            &[self.to_screen(-MAX_G, 0f32), self.to_screen(MAX_G, 0f32)],
+            1f32,
+        )?;
+        graphics::line(
+            ctx,
+            &[self.to_screen(0f32, -MAX_G), self.to_screen(0f32, MAX_G)],
+            1f32,
+        )?;
+        for ring in 1..(MAX_G as i32 + 1) {
+            graphics::circle(
+                ctx,
+                DrawMode::Line(1f32),
+                self.center,
+                ring as f32 * self.scale,
+                0.5,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn draw_envelope(&self, ctx: &mut Context, envelope: &Envelope) -> GameResult<()> {
+        let mut points: Vec<Point2> = (0..SECTORS)
+            .filter_map(|sector| {
+                envelope.radius(sector).map(|radius| {
+                    let angle = sector_angle(sector);
+                    self.to_screen(radius * angle.cos(), radius * angle.sin())
+                })
+            })
+            .collect();
+
+        if points.len() > 2 {
+            let first = points[0];
+            points.push(first);
+            graphics::line(ctx, &points, 2f32)?;
+        }
+        Ok(())
+    }
+}
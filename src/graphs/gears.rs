@@ -2,6 +2,11 @@ use super::nets::*;
 use super::*;
 use ggez::graphics::*;
 use ggez::*;
+use std::mem;
+use util::*;
+
+//km/h per grip bucket
+const GRIP_SPEED_STEP: i32 = 10;
 
 pub struct Ratio {
     pub gear: i32,
@@ -17,6 +22,94 @@ pub struct StupidGraphData {
     pub longtitudal_acceleration: GraphLine,
     pub braking_acceleration: GraphLine,
     pub track_length: f32,
+    pub grip: GripBySpeed,
+    pub grip_reference: Option<GripBySpeed>,
+}
+
+/// Combined g against speed, split by what the car was doing,
+/// flat curve is mechanical grip, rising one is aero. High percentile per speed, not peak.
+pub struct GripBySpeed {
+    pub cornering: GraphLine,
+    pub braking: GraphLine,
+    pub traction: GraphLine,
+    //cornering, braking and traction
+    samples: [PeakGrip; 3],
+    pub max_speed: f32,
+    pub wings: [f32; 2],
+}
+
+impl GripBySpeed {
+    pub fn new() -> GripBySpeed {
+        GripBySpeed {
+            cornering: GraphLine::new(GRIP_SPEED_STEP, true, false, GraphRegion::Left, 2),
+            braking: GraphLine::new(GRIP_SPEED_STEP, true, false, GraphRegion::Left, 2),
+            traction: GraphLine::new(GRIP_SPEED_STEP, true, false, GraphRegion::Left, 2),
+            samples: [
+                PeakGrip::new(GRIP_SPEED_STEP),
+                PeakGrip::new(GRIP_SPEED_STEP),
+                PeakGrip::new(GRIP_SPEED_STEP),
+            ],
+            max_speed: 100f32,
+            wings: [0f32; 2],
+        }
+    }
+
+    /// Accelerations in g, positive longtitudal is braking, speed in km/h.
+    pub fn add(&mut self, lateral: f32, longtitudal: f32, speed: f32, wings: [f32; 2]) {
+        let combined = (lateral.powi(2) + longtitudal.powi(2)).sqrt();
+        let speed_bucket = speed as i32;
+
+        self.max_speed = self.max_speed.max(speed);
+        self.wings = wings;
+
+        let kind = if lateral.abs() >= longtitudal.abs() {
+            0
+        } else if longtitudal > 0f32 {
+            1
+        } else {
+            2
+        };
+        let grip = self.samples[kind].add(speed_bucket, combined);
+        let line = match kind {
+            0 => &mut self.cornering,
+            1 => &mut self.braking,
+            _ => &mut self.traction,
+        };
+        //dot shows the sample, curve the percentile
+        line.add(speed_bucket, combined, true);
+        line.set(speed_bucket, grip);
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        max_values: &Point2,
+        faded: bool,
+    ) -> GameResult<()> {
+        let color = |line: Color, dot: Color| -> (Color, Color) {
+            if faded {
+                let mut line = line;
+                line.a = 0.35;
+                (line, line)
+            } else {
+                (line, dot)
+            }
+        };
+
+        let (line, dot) = color(Color::from_rgb(128, 0, 255), Color::from_rgb(177, 100, 255));
+        self.cornering
+            .draw(ctx, line, dot, screen_size, max_values)?;
+
+        let (line, dot) = color(Color::from_rgb(236, 87, 15), Color::from_rgb(250, 0, 0));
+        self.braking.draw(ctx, line, dot, screen_size, max_values)?;
+
+        let (line, dot) = color(Color::from_rgb(34, 177, 76), Color::from_rgb(128, 255, 0));
+        self.traction
+            .draw(ctx, line, dot, screen_size, max_values)?;
+
+        Ok(())
+    }
 }
 
 impl StupidGraphData {
@@ -34,6 +127,8 @@ impl StupidGraphData {
             max_rotations: 1f32,
             max_rotations_rpm: 0f32,
             track_length,
+            grip: GripBySpeed::new(),
+            grip_reference: None,
         }
     }
 
@@ -69,6 +164,8 @@ impl StupidGraphData {
         position: f32,
         lateral: f32,
         longtitudal: f32,
+        speed: f32,
+        wings: [f32; 2],
         input: &Inputs,
         crash_state: u32,
     ) {
@@ -76,6 +173,11 @@ impl StupidGraphData {
         let throttle = input.throttle > 0.01;
         let brake = input.brake > 0.01;
 
+        if !crash && (lateral.abs() / 9.8) < 10f32 && (longtitudal.abs() / 9.8) < 10f32 {
+            self.grip
+                .add(lateral / 9.8, longtitudal / 9.8, speed * 3.6, wings);
+        }
+
         if (lateral.abs() / 9.8) < 10f32 {
             self.lateral_acceleration
                 .add(position as i32, lateral.abs() / 9.8, crash);
@@ -100,6 +202,80 @@ impl StupidGraphData {
         }
     }
 
+    /// Keeps current grip curves as a reference, to compare against another wing setting.
+    pub fn pin_grip_reference(&mut self) {
+        let grip = mem::replace(&mut self.grip, GripBySpeed::new());
+        self.grip_reference = Some(grip);
+    }
+
+    pub fn draw_grip(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+    ) -> GameResult<()> {
+        let max_speed = self
+            .grip_reference
+            .as_ref()
+            .map(|r| r.max_speed)
+            .unwrap_or(0f32)
+            .max(self.grip.max_speed);
+        let max_speed = (max_speed / 50f32).ceil() * 50f32;
+        let max_g = 4f32;
+        let max_values = Point2::new(max_speed, max_g);
+
+        graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+        for g in 1..(max_g as i32) {
+            let y = g as f32 / max_g;
+            graphics::line(
+                ctx,
+                &[
+                    scale_left(0f32, y, screen_size),
+                    scale_left(1f32, y, screen_size),
+                ],
+                1f32,
+            )?;
+            labels.draw(ctx, &format!("{}g", g), scale_left(0f32, y, screen_size))?;
+        }
+        for speed in (50..(max_speed as i32)).step_by(50) {
+            let x = speed as f32 / max_speed;
+            graphics::line(
+                ctx,
+                &[
+                    scale_left(x, 0f32, screen_size),
+                    scale_left(x, 1f32, screen_size),
+                ],
+                1f32,
+            )?;
+            labels.draw(
+                ctx,
+                &format!("{} km/h", speed),
+                scale_left(x, 0.03, screen_size),
+            )?;
+        }
+
+        if let Some(ref mut reference) = self.grip_reference {
+            reference.draw(ctx, screen_size, &max_values, true)?;
+        }
+        self.grip.draw(ctx, screen_size, &max_values, false)?;
+
+        let mut legend = format!(
+            "cornering (purple), braking (red), traction (green) vs speed, wings {:.2}/{:.2}",
+            self.grip.wings[0], self.grip.wings[1]
+        );
+        if let Some(ref reference) = self.grip_reference {
+            legend.push_str(&format!(
+                ", faded: wings {:.2}/{:.2}",
+                reference.wings[0], reference.wings[1]
+            ));
+        }
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(ctx, &legend, scale_left(0.01, 1f32, screen_size))?;
+
+        Ok(())
+    }
+
     pub fn draw(
         &mut self,
         ctx: &mut Context,
@@ -0,0 +1,208 @@
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use super::Inputs;
+use std::mem;
+use util::*;
+
+//meters between stored samples
+const SAMPLE_STEP: f32 = 1f32;
+
+pub struct InputsLap {
+    samples: Vec<(f32, Inputs)>,
+    pub time: f32,
+}
+
+impl InputsLap {
+    fn new() -> InputsLap {
+        InputsLap {
+            samples: Vec::new(),
+            time: 0f32,
+        }
+    }
+}
+
+pub struct InputsGraphData {
+    current: InputsLap,
+    reference: Option<InputsLap>,
+    current_lap: i32,
+    invalid: bool,
+    pub show_unfiltered: bool,
+}
+
+impl InputsGraphData {
+    pub fn new() -> InputsGraphData {
+        InputsGraphData {
+            current: InputsLap::new(),
+            reference: None,
+            current_lap: 0,
+            invalid: false,
+            show_unfiltered: true,
+        }
+    }
+
+    pub fn add(
+        &mut self,
+        lap: i32,
+        distance: f32,
+        inputs: &Inputs,
+        last_lap_time: f32,
+        lap_invalidated: bool,
+        track_length: f32,
+    ) {
+        if lap != self.current_lap {
+            let mut finished = mem::replace(&mut self.current, InputsLap::new());
+            finished.time = last_lap_time;
+
+            let covered = finished.samples.last().map(|s| s.0).unwrap_or(0f32);
+            let complete = covered > track_length * 0.9 && last_lap_time > 0f32;
+            let faster = self
+                .reference
+                .as_ref()
+                .map(|r| last_lap_time < r.time)
+                .unwrap_or(true);
+
+            if complete && !self.invalid && faster {
+                self.reference = Some(finished);
+            }
+
+            self.current_lap = lap;
+            self.invalid = false;
+        }
+
+        self.invalid = self.invalid || lap_invalidated;
+
+        let far_enough = self
+            .current
+            .samples
+            .last()
+            .map(|s| distance - s.0 >= SAMPLE_STEP)
+            .unwrap_or(true);
+
+        if far_enough && distance >= 0f32 {
+            self.current.samples.push((distance, inputs.clone()));
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+        track_length: f32,
+    ) -> GameResult<()> {
+        let grid = (1, 4);
+        let traces: [(&str, fn(&Inputs) -> f32, Option<fn(&Inputs) -> f32>, Color); 4] = [
+            (
+                "Throttle",
+                |i| i.throttle,
+                Some(|i| i.unfiltered_throttle),
+                Color::from_rgb(147, 197, 67),
+            ),
+            (
+                "Brake",
+                |i| i.brake,
+                Some(|i| i.unfiltered_brake),
+                Color::from_rgb(236, 87, 15),
+            ),
+            ("Clutch", |i| i.clutch, None, Color::from_rgb(0, 191, 255)),
+            (
+                "Steering",
+                |i| (i.steering + 1f32) / 2f32,
+                None,
+                Color::from_rgb(177, 100, 255),
+            ),
+        ];
+
+        for (row, (name, value, unfiltered, color)) in traces.iter().enumerate() {
+            let cell = (0, row);
+            let to_screen = |distance: f32, y: f32| -> Point2 {
+                scale_left_cell(distance / track_length, y, cell, grid, screen_size)
+            };
+
+            graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+            graphics::line(
+                ctx,
+                &[to_screen(0f32, 0f32), to_screen(track_length, 0f32)],
+                1f32,
+            )?;
+            graphics::line(
+                ctx,
+                &[to_screen(0f32, 1f32), to_screen(track_length, 1f32)],
+                1f32,
+            )?;
+
+            if let Some(ref reference) = self.reference {
+                let mut faded = *color;
+                faded.a = 0.35;
+                draw_trace(ctx, &reference.samples, value, faded, &to_screen, 2f32)?;
+            }
+
+            if self.show_unfiltered {
+                if let Some(unfiltered) = unfiltered {
+                    draw_trace(
+                        ctx,
+                        &self.current.samples,
+                        unfiltered,
+                        WHITE,
+                        &to_screen,
+                        1f32,
+                    )?;
+                }
+            }
+
+            draw_trace(ctx, &self.current.samples, value, *color, &to_screen, 2f32)?;
+
+            graphics::set_color(ctx, WHITE)?;
+            labels.draw(ctx, name, to_screen(0f32, 1.1))?;
+        }
+
+        let legend = match (self.reference.as_ref(), self.show_unfiltered) {
+            (Some(reference), true) => format!(
+                "faded: best lap {:.3}s, white: unfiltered input",
+                reference.time
+            ),
+            (Some(reference), false) => format!("faded: best lap {:.3}s", reference.time),
+            (None, true) => "white: unfiltered input".to_string(),
+            (None, false) => String::new(),
+        };
+
+        if !legend.is_empty() {
+            graphics::set_color(ctx, WHITE)?;
+            labels.draw(ctx, &legend, scale_left(0.5, 1f32, screen_size))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn draw_trace<F>(
+    ctx: &mut Context,
+    samples: &[(f32, Inputs)],
+    value: &fn(&Inputs) -> f32,
+    color: Color,
+    to_screen: &F,
+    width: f32,
+) -> GameResult<()>
+where
+    F: Fn(f32, f32) -> Point2,
+{
+    if samples.len() < 2 {
+        return Ok(());
+    }
+
+    //no need for more points than there are pixels
+    let stride = (samples.len() / 800).max(1);
+
+    let points: Vec<Point2> = samples
+        .iter()
+        .step_by(stride)
+        .map(|(distance, inputs)| to_screen(*distance, value(inputs).max(0f32).min(1f32)))
+        .collect();
+
+    graphics::set_color(ctx, color)?;
+    graphics::line(ctx, &points, width)?;
+
+    Ok(())
+}
@@ -1,11 +1,37 @@
 pub mod nets;
 
+mod aero;
+mod balance;
+mod damage;
+mod dampers;
+mod details;
+mod engine;
+mod friction;
 mod gears;
+mod inputs;
+mod multiclass;
+mod rival;
 mod rollndiff;
-
+mod slip;
+mod standings;
+mod strategy;
+
+pub use self::aero::*;
+pub use self::balance::*;
+pub use self::damage::*;
+pub use self::dampers::*;
+pub use self::details::*;
+pub use self::engine::*;
+pub use self::friction::*;
 pub use self::gears::*;
+pub use self::inputs::*;
+pub use self::multiclass::*;
 use self::nets::*;
+pub use self::rival::*;
 pub use self::rollndiff::*;
+pub use self::slip::*;
+pub use self::standings::*;
+pub use self::strategy::*;
 
 use definitions::*;
 use ggez::graphics::*;
@@ -23,6 +49,8 @@ pub struct GraphLine {
     pub current_value: (i32, f32),
     pub max_value: f32,
     pub cache: Option<Mesh>,
+    //screen size and axis maxima cache was built for
+    cache_scale: (Point2, Point2),
     region: GraphRegion,
     smoothening: usize,
     line_width: f32,
@@ -54,6 +82,7 @@ impl GraphLine {
             current_value: (0, 0f32),
             max_value: 1f32,
             cache: None,
+            cache_scale: (Point2::new(0f32, 0f32), Point2::new(0f32, 0f32)),
             region,
             smoothening,
             line_width: 2f32,
@@ -100,6 +129,22 @@ impl GraphLine {
         }
     }
 
+    /// Replaces value of x's step, unlike `add` it can go down.
+    pub fn set(&mut self, x: i32, y: f32) {
+        let step_x = x - x % self.step;
+        if self.values.insert(step_x, y) != Some(y) {
+            self.cache = None;
+        }
+    }
+
+    /// Highest value seen and where, current only values are not counted.
+    pub fn peak(&self) -> Option<(i32, f32)> {
+        self.values
+            .iter()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(Ordering::Equal))
+            .map(|(x, y)| (*x, *y))
+    }
+
     pub fn draw(
         &mut self,
         ctx: &mut Context,
@@ -108,8 +153,12 @@ impl GraphLine {
         screen_size: &Point2,
         max_values: &Point2,
     ) -> GameResult<()> {
+        if self.cache_scale != (*screen_size, *max_values) {
+            self.cache = None;
+        }
         if self.values.len() > self.smoothening {
             if self.cache.is_none() {
+                self.cache_scale = (*screen_size, *max_values);
                 let values = self.values.iter().collect::<Vec<_>>();
 
                 let smooth = values
@@ -287,6 +336,10 @@ pub struct Inputs {
     pub brake: f32,
     pub clutch: f32,
     pub steering: f32,
+    pub unfiltered_throttle: f32,
+    pub unfiltered_brake: f32,
+    pub unfiltered_clutch: f32,
+    pub unfiltered_steering: f32,
 }
 
 impl Inputs {
@@ -296,6 +349,10 @@ impl Inputs {
             brake: sm.mBrake,
             clutch: sm.mClutch,
             steering: sm.mSteering,
+            unfiltered_throttle: sm.mUnfilteredThrottle,
+            unfiltered_brake: sm.mUnfilteredBrake,
+            unfiltered_clutch: sm.mUnfilteredClutch,
+            unfiltered_steering: sm.mUnfilteredSteering,
         }
     }
 }
@@ -0,0 +1,282 @@
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use super::standings::*;
+use std::f32;
+use util::*;
+
+const ROWS: usize = 32;
+const MIN_WARNING: f32 = 1f32;
+const MAX_WARNING: f32 = 10f32;
+//m/s, parked cars are not traffic
+const MIN_TRAFFIC_SPEED: f32 = 10f32;
+//m/s, a car gaining slower than this is not coming through
+const MIN_CLOSING_SPEED: f32 = 0.5;
+//meters in the world, longest warning at any sane closing speed stays inside
+const NEAR_RADIUS: f32 = 300f32;
+
+/// Cars of one class, in standings order.
+pub struct ClassGroup<'a> {
+    pub name: &'a str,
+    pub best_lap: Option<f32>,
+    pub entries: Vec<&'a Entry>,
+}
+
+/// Faster class car behind the viewed one on track.
+pub struct Traffic<'a> {
+    pub entry: &'a Entry,
+    pub seconds: f32,
+}
+
+pub struct MulticlassData {
+    /// Seconds behind on track that trigger the warning.
+    pub warning_seconds: f32,
+}
+
+impl MulticlassData {
+    pub fn new() -> MulticlassData {
+        MulticlassData {
+            warning_seconds: 3f32,
+        }
+    }
+
+    pub fn adjust(&mut self, seconds: f32) {
+        self.warning_seconds = (self.warning_seconds + seconds)
+            .max(MIN_WARNING)
+            .min(MAX_WARNING);
+    }
+
+    /// Fastest class first, by best lap anyone in class has done.
+    pub fn classes<'a>(&self, standings: &'a StandingsData) -> Vec<ClassGroup<'a>> {
+        let mut classes: Vec<ClassGroup<'a>> = Vec::new();
+
+        for entry in standings.entries.iter() {
+            let found = classes.iter().position(|c| c.name == entry.class.as_str());
+            let ix = match found {
+                Some(ix) => ix,
+                None => {
+                    classes.push(ClassGroup {
+                        name: &entry.class,
+                        best_lap: None,
+                        entries: Vec::new(),
+                    });
+                    classes.len() - 1
+                }
+            };
+
+            let class = &mut classes[ix];
+            if entry.best_lap > 0f32 {
+                class.best_lap = Some(
+                    class
+                        .best_lap
+                        .map(|b| b.min(entry.best_lap))
+                        .unwrap_or(entry.best_lap),
+                );
+            }
+            class.entries.push(entry);
+        }
+
+        classes.sort_by(|a, b| {
+            let a = a.best_lap.unwrap_or(f32::MAX);
+            let b = b.best_lap.unwrap_or(f32::MAX);
+            a.partial_cmp(&b).unwrap()
+        });
+        classes
+    }
+
+    /// Cars of faster classes that are less than warning seconds behind on track.
+    pub fn traffic<'a>(&self, standings: &'a StandingsData) -> Vec<Traffic<'a>> {
+        let viewed = match standings.viewed_entry() {
+            Some(viewed) => viewed,
+            None => return Vec::new(),
+        };
+
+        let classes = self.classes(standings);
+        let rank = |class: &str| classes.iter().position(|c| c.name == class);
+        let own_rank = match rank(&viewed.class) {
+            Some(own_rank) => own_rank,
+            None => return Vec::new(),
+        };
+        //only faster classes that have set a time, unknown pace is not faster
+        let own_best = classes[own_rank].best_lap;
+
+        let mut traffic: Vec<Traffic<'a>> = standings
+            .entries
+            .iter()
+            .filter(|e| e.index != viewed.index && !e.in_pits())
+            .filter(|e| e.speed > MIN_TRAFFIC_SPEED)
+            .filter(|e| match rank(&e.class) {
+                Some(r) => r < own_rank && classes[r].best_lap.is_some() && own_best.is_some(),
+                None => false,
+            })
+            .filter_map(|e| {
+                //meters the other car is behind on track, laps do not matter here
+                let length = standings.track_length;
+                let behind = ((viewed.lap_distance - e.lap_distance) % length + length) % length;
+                let closing = e.speed - viewed.speed;
+                if closing < MIN_CLOSING_SPEED {
+                    return None;
+                }
+                let seconds = behind / closing;
+
+                //lap distance can jump around pit lane, cars must be near in the world too,
+                //straight line is never longer than track distance, so a fixed radius
+                let dx = viewed.world.x - e.world.x;
+                let dy = viewed.world.y - e.world.y;
+                let dz = viewed.world.z - e.world.z;
+                let near = (dx * dx + dy * dy + dz * dz).sqrt() < NEAR_RADIUS;
+
+                if near && seconds < self.warning_seconds {
+                    Some(Traffic { entry: e, seconds })
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        traffic.sort_by(|a, b| a.seconds.partial_cmp(&b.seconds).unwrap());
+        traffic
+    }
+
+    /// Line under max power and rpm, visible from any panel.
+    pub fn draw_warning(
+        &self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+        standings: &StandingsData,
+    ) -> GameResult<()> {
+        let traffic = self.traffic(standings);
+        if traffic.is_empty() {
+            return Ok(());
+        }
+
+        let line = traffic
+            .iter()
+            .map(|t| {
+                format!(
+                    "{} ({}) {:.1}s behind",
+                    t.entry.name, t.entry.class, t.seconds
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("   ");
+
+        graphics::set_color(ctx, Color::from_rgb(0, 191, 255))?;
+        labels.draw(
+            ctx,
+            &format!("Faster class: {}", line),
+            Point2::new(screen_size.x * 0.01, screen_size.y * 0.035),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+        standings: &StandingsData,
+    ) -> GameResult<()> {
+        let line_height = 1f32 / (ROWS + 2) as f32;
+        let columns = [0.01, 0.07, 0.13, 0.38, 0.48, 0.58, 0.70];
+        let header = [
+            "In class",
+            "Overall",
+            "Name",
+            "Class gap",
+            "Interval",
+            "Last",
+            "Best",
+        ];
+
+        graphics::set_color(ctx, WHITE)?;
+        for (x, title) in columns.iter().zip(header.iter()) {
+            labels.draw(ctx, title, scale_left(*x, 1f32, screen_size))?;
+        }
+
+        let viewed = standings.viewed_entry().map(|e| e.index);
+        let mut row = 1;
+
+        for class in self.classes(standings).iter() {
+            if row >= ROWS {
+                break;
+            }
+
+            graphics::set_color(ctx, Color::from_rgb(0, 191, 255))?;
+            let title = format!(
+                "{}, {} cars, best {}",
+                if class.name.is_empty() {
+                    "-"
+                } else {
+                    class.name
+                },
+                class.entries.len(),
+                format_lap(class.best_lap.unwrap_or(0f32))
+            );
+            labels.draw(
+                ctx,
+                &title,
+                scale_left(0.01, 1f32 - line_height * row as f32, screen_size),
+            )?;
+            row += 1;
+
+            let leader = class.entries[0];
+            for (n, entry) in class.entries.iter().enumerate() {
+                if row >= ROWS {
+                    break;
+                }
+
+                let (gap, interval) = if n == 0 {
+                    (String::new(), String::new())
+                } else {
+                    (
+                        standings.format_gap(entry, leader),
+                        standings.format_gap(entry, class.entries[n - 1]),
+                    )
+                };
+
+                let cells = [
+                    format!("{}", n + 1),
+                    format!("{}", entry.position),
+                    entry.name.clone(),
+                    gap,
+                    interval,
+                    format_lap(entry.last_lap),
+                    format_lap(entry.best_lap),
+                ];
+
+                let color = if Some(entry.index) == viewed {
+                    Color::from_rgb(255, 201, 14)
+                } else if entry.in_pits() {
+                    Color::from_rgb(127, 127, 127)
+                } else {
+                    WHITE
+                };
+
+                graphics::set_color(ctx, color)?;
+                let y = 1f32 - line_height * row as f32;
+                for (x, cell) in columns.iter().zip(cells.iter()) {
+                    if !cell.is_empty() {
+                        labels.draw(ctx, cell, scale_left(*x, y, screen_size))?;
+                    }
+                }
+                row += 1;
+            }
+        }
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(
+            ctx,
+            &format!(
+                "Faster class warning within {:.0}s, left/right to change",
+                self.warning_seconds
+            ),
+            scale_left(0.01, 0.02, screen_size),
+        )?;
+
+        Ok(())
+    }
+}
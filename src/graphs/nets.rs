@@ -125,6 +125,7 @@ impl NetsAndBorders {
         numeric_cache: &NumericTextCache,
         max_rh: f32,
         min_rh: f32,
+        left_nets: bool,
     ) -> GameResult<()> {
         let draw_digit =
             |ctx: &mut Context, mut point: Point2, digit: i32, fat: bool| -> GameResult<()> {
@@ -216,6 +217,10 @@ impl NetsAndBorders {
             graphics::draw(ctx, &self.right_region_horizontal, dest, 0f32)?;
         }
 
+        if !left_nets {
+            return Ok(());
+        }
+
         for accel in 1..11 {
             let y = accel as f32 / 10f32;
             let dest = scale_left(0f32, y, screen_size);
@@ -281,3 +286,22 @@ pub fn scale_right_bottom(x: f32, y: f32, scr_size: &Point2) -> Point2 {
         (scr_size.y * 0.525) + (1.0 - y) * (scr_size.y * 0.475),
     )
 }
+
+/// Same as `scale_left`, but inside one cell of a `grid.0 x grid.1` split of the left region,
+/// cells are counted from top left.
+#[inline]
+pub fn scale_left_cell(
+    x: f32,
+    y: f32,
+    cell: (usize, usize),
+    grid: (usize, usize),
+    scr_size: &Point2,
+) -> Point2 {
+    let (col, row) = cell;
+    let (cols, rows) = grid;
+
+    let cell_x = (col as f32 + 0.05 + x * 0.9) / cols as f32;
+    let cell_y = (rows as f32 - row as f32 - 1f32 + 0.05 + y * 0.8) / rows as f32;
+
+    scale_left(cell_x, cell_y, scr_size)
+}
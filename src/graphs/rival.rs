@@ -0,0 +1,287 @@
+use definitions::*;
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use super::standings::*;
+use std::collections::BTreeMap;
+use util::*;
+
+const ROWS: usize = 26;
+
+/// Sector and lap times of one lap, whatever has been seen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LapSectors {
+    pub sectors: [Option<f32>; 3],
+    pub lap: Option<f32>,
+}
+
+/// Times of every participant for the whole session, so a rival can be picked any time.
+pub struct RivalData {
+    pub rival: Option<usize>,
+    laps: Vec<BTreeMap<i32, LapSectors>>,
+    fastest: Vec<[f32; 3]>,
+    //sector and lap it was started on, as last seen
+    sector: Vec<(i32, i32)>,
+}
+
+impl RivalData {
+    pub fn new() -> RivalData {
+        RivalData {
+            rival: None,
+            laps: (0..STORED_PARTICIPANTS_MAX)
+                .map(|_| BTreeMap::new())
+                .collect(),
+            fastest: vec![[0f32; 3]; STORED_PARTICIPANTS_MAX],
+            sector: vec![(-1, 0); STORED_PARTICIPANTS_MAX],
+        }
+    }
+
+    pub fn update(&mut self, sm: &SharedMemory) {
+        for ix in 0..sm.participant_count() {
+            let info = &sm.mParticipantInfo.data[ix];
+            if !info.is_active() {
+                continue;
+            }
+
+            self.fastest[ix] = [
+                sm.mFastestSector1Times.data[ix],
+                sm.mFastestSector2Times.data[ix],
+                sm.mFastestSector3Times.data[ix],
+            ];
+
+            let current = [
+                sm.mCurrentSector1Times.data[ix],
+                sm.mCurrentSector2Times.data[ix],
+                sm.mCurrentSector3Times.data[ix],
+            ];
+
+            let (last_sector, last_lap) = self.sector[ix];
+            let sector = info.mCurrentSector;
+
+            //sector just finished belongs to the lap it was started on
+            if sector != last_sector && last_sector >= 0 && last_sector < 3 {
+                let time = current[last_sector as usize];
+                let lap = self.laps[ix]
+                    .entry(last_lap)
+                    .or_insert_with(LapSectors::default);
+                if time > 0f32 {
+                    lap.sectors[last_sector as usize] = Some(time);
+                }
+                if last_sector == 2 && sm.mLastLapTimes.data[ix] > 0f32 {
+                    lap.lap = Some(sm.mLastLapTimes.data[ix]);
+                }
+            }
+
+            self.sector[ix] = (sector, info.mCurrentLap);
+        }
+    }
+
+    /// Next or previous car in standings, viewed one is skipped.
+    pub fn select(&mut self, standings: &StandingsData, up: bool) {
+        let viewed = standings.viewed_entry().map(|e| e.index);
+        let candidates: Vec<usize> = standings
+            .entries
+            .iter()
+            .map(|e| e.index)
+            .filter(|ix| Some(*ix) != viewed)
+            .collect();
+
+        if candidates.is_empty() {
+            self.rival = None;
+            return;
+        }
+
+        let current = self
+            .rival
+            .and_then(|r| candidates.iter().position(|c| *c == r));
+
+        let next = match current {
+            Some(pos) if up => (pos + candidates.len() - 1) % candidates.len(),
+            Some(pos) => (pos + 1) % candidates.len(),
+            None => 0,
+        };
+
+        self.rival = Some(candidates[next]);
+    }
+
+    /// Chosen rival, or the car ahead of viewed one until something is chosen.
+    fn rival_entry<'a>(&self, standings: &'a StandingsData) -> Option<&'a Entry> {
+        match self.rival {
+            Some(rival) => standings.entries.iter().find(|e| e.index == rival),
+            None => {
+                let viewed = standings.viewed?;
+                let ahead = if viewed > 0 { viewed - 1 } else { viewed + 1 };
+                standings.entries.get(ahead)
+            }
+        }
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+        standings: &StandingsData,
+    ) -> GameResult<()> {
+        let line_height = 1f32 / (ROWS + 8) as f32;
+        let faster = Color::from_rgb(86, 226, 86);
+        let slower = Color::from_rgb(250, 0, 0);
+        let delta_color = |delta: f32| if delta <= 0f32 { faster } else { slower };
+
+        let (me, rival) = match (standings.viewed_entry(), self.rival_entry(standings)) {
+            (Some(me), Some(rival)) => (me, rival),
+            _ => {
+                graphics::set_color(ctx, WHITE)?;
+                labels.draw(
+                    ctx,
+                    "No rival to compare",
+                    scale_left(0.01, 1f32, screen_size),
+                )?;
+                return Ok(());
+            }
+        };
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(
+            ctx,
+            &format!(
+                "{} (P{}) vs {} (P{}), up/down to pick rival, green: you are faster",
+                me.name, me.position, rival.name, rival.position
+            ),
+            scale_left(0.01, 1f32, screen_size),
+        )?;
+
+        //best sectors and lap
+        let columns = [0.01, 0.2, 0.38, 0.56, 0.74];
+        let mine = self.fastest[me.index];
+        let theirs = self.fastest[rival.index];
+        let y = 1f32 - line_height * 2f32;
+        for (x, title) in columns.iter().zip(["Best", "S1", "S2", "S3", "Lap"].iter()) {
+            labels.draw(ctx, title, scale_left(*x, y, screen_size))?;
+        }
+
+        let best = [
+            (mine[0], theirs[0]),
+            (mine[1], theirs[1]),
+            (mine[2], theirs[2]),
+            (me.best_lap, rival.best_lap),
+        ];
+        let y = 1f32 - line_height * 3f32;
+        for (x, &(mine, theirs)) in columns.iter().skip(1).zip(best.iter()) {
+            draw_compare(ctx, labels, screen_size, *x, y, mine, theirs, &delta_color)?;
+        }
+
+        //lap by lap, newest first
+        let y = 1f32 - line_height * 5f32;
+        graphics::set_color(ctx, WHITE)?;
+        for (x, title) in columns
+            .iter()
+            .zip(["Lap", "S1", "S2", "S3", "Lap time"].iter())
+        {
+            labels.draw(ctx, title, scale_left(*x, y, screen_size))?;
+        }
+
+        let my_laps = &self.laps[me.index];
+        let their_laps = &self.laps[rival.index];
+        let mut totals = [(0f32, 0u32); 4];
+
+        for (lap, mine) in my_laps.iter() {
+            if let Some(theirs) = their_laps.get(lap) {
+                for s in 0..3 {
+                    if let (Some(a), Some(b)) = (mine.sectors[s], theirs.sectors[s]) {
+                        totals[s].0 += a - b;
+                        totals[s].1 += 1;
+                    }
+                }
+                if let (Some(a), Some(b)) = (mine.lap, theirs.lap) {
+                    totals[3].0 += a - b;
+                    totals[3].1 += 1;
+                }
+            }
+        }
+
+        for (n, (lap, mine)) in my_laps.iter().rev().take(ROWS).enumerate() {
+            let y = 1f32 - line_height * (n + 6) as f32;
+            let theirs = their_laps.get(lap).cloned().unwrap_or_default();
+
+            graphics::set_color(ctx, WHITE)?;
+            labels.draw(
+                ctx,
+                &format!("{}", lap),
+                scale_left(columns[0], y, screen_size),
+            )?;
+
+            let pairs = [
+                (mine.sectors[0], theirs.sectors[0]),
+                (mine.sectors[1], theirs.sectors[1]),
+                (mine.sectors[2], theirs.sectors[2]),
+                (mine.lap, theirs.lap),
+            ];
+            for (x, &(mine, theirs)) in columns.iter().skip(1).zip(pairs.iter()) {
+                draw_compare(
+                    ctx,
+                    labels,
+                    screen_size,
+                    *x,
+                    y,
+                    mine.unwrap_or(0f32),
+                    theirs.unwrap_or(0f32),
+                    &delta_color,
+                )?;
+            }
+        }
+
+        //average delta on laps both have done
+        let y = 1f32 - line_height * (ROWS + 7) as f32;
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(ctx, "Average", scale_left(columns[0], y, screen_size))?;
+        for (x, &(sum, count)) in columns.iter().skip(1).zip(totals.iter()) {
+            if count > 0 {
+                let delta = sum / count as f32;
+                graphics::set_color(ctx, delta_color(delta))?;
+                labels.draw(
+                    ctx,
+                    &format!("{:+.3} ({} laps)", delta, count),
+                    scale_left(*x, y, screen_size),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Own time with delta to rival, or whatever is known.
+fn draw_compare<F>(
+    ctx: &mut Context,
+    labels: &mut TextCache,
+    screen_size: &Point2,
+    x: f32,
+    y: f32,
+    mine: f32,
+    theirs: f32,
+    delta_color: &F,
+) -> GameResult<()>
+where
+    F: Fn(f32) -> Color,
+{
+    let text = match (mine > 0f32, theirs > 0f32) {
+        (true, true) => {
+            graphics::set_color(ctx, delta_color(mine - theirs))?;
+            format!("{} {:+.3}", format_lap(mine), mine - theirs)
+        }
+        (true, false) => {
+            graphics::set_color(ctx, WHITE)?;
+            format_lap(mine)
+        }
+        (false, true) => {
+            graphics::set_color(ctx, WHITE)?;
+            format!("- / {}", format_lap(theirs))
+        }
+        (false, false) => return Ok(()),
+    };
+
+    labels.draw(ctx, &text, scale_left(x, y, screen_size))?;
+    Ok(())
+}
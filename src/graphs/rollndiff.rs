@@ -3,14 +3,492 @@ use ggez::graphics::*;
 use ggez::*;
 
 use smallvec::SmallVec;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::time::Duration;
 
 use super::nets::*;
+use super::{Inputs, Ratio};
+use util::*;
+
+//nothing in shared memory tells us those, so typical race car numbers, meters
+pub const NOMINAL_TRACK_WIDTH: f32 = 1.6;
+pub const NOMINAL_WHEELBASE: f32 = 2.6;
+
+//how much of the travel is considered static ride height per sample
+const BASELINE_SMOOTHING: f32 = 0.002;
+const SCATTER_LENGTH: usize = 600;
+
+pub struct RollGraphData {
+    travel: TyresArray<f32>,
+    baseline: Option<TyresArray<f32>>,
+    roll_fit: LinearFit,
+    pitch_fit: LinearFit,
+    roll_scatter: VecDeque<(f32, f32)>,
+    pitch_scatter: VecDeque<(f32, f32)>,
+    reference: Option<(Option<f32>, Option<f32>)>,
+}
+
+impl RollGraphData {
+    pub fn new() -> RollGraphData {
+        RollGraphData {
+            travel: TyresArray { data: [0f32; 4] },
+            baseline: None,
+            roll_fit: LinearFit::new(),
+            pitch_fit: LinearFit::new(),
+            roll_scatter: VecDeque::with_capacity(SCATTER_LENGTH),
+            pitch_scatter: VecDeque::with_capacity(SCATTER_LENGTH),
+            reference: None,
+        }
+    }
+
+    /// Travel in meters, lateral and longtitudal acceleration in g, positive longtitudal is braking.
+    pub fn add(&mut self, travel: &TyresArray<f32>, lateral: f32, longtitudal: f32) {
+        self.travel = travel.clone();
+
+        let mut baseline = self.baseline.take().unwrap_or(travel.clone());
+        for (b, t) in baseline.data.iter_mut().zip(travel.data.iter()) {
+            *b += (*t - *b) * BASELINE_SMOOTHING;
+        }
+        self.baseline = Some(baseline);
+
+        let roll = roll_angle(travel);
+        let pitch = pitch_angle(travel);
+
+        if lateral.abs() > 0.2 && longtitudal.abs() < 0.3 {
+            self.roll_fit.add(lateral, roll);
+            push_limited(&mut self.roll_scatter, (lateral, roll));
+        }
+
+        if longtitudal > 0.2 && lateral.abs() < 0.3 {
+            self.pitch_fit.add(longtitudal, pitch);
+            push_limited(&mut self.pitch_scatter, (longtitudal, pitch));
+        }
+    }
+
+    /// Degrees of roll per g of lateral acceleration.
+    pub fn roll_gradient(&self) -> Option<f32> {
+        self.roll_fit.slope().map(|s| s.abs())
+    }
+
+    /// Degrees of pitch per g of braking.
+    pub fn pitch_gradient(&self) -> Option<f32> {
+        self.pitch_fit.slope().map(|s| s.abs())
+    }
+
+    /// Keeps current gradients for comparison and starts fitting from scratch.
+    pub fn pin_reference(&mut self) {
+        self.reference = Some((self.roll_gradient(), self.pitch_gradient()));
+        self.roll_fit = LinearFit::new();
+        self.pitch_fit = LinearFit::new();
+        self.roll_scatter.clear();
+        self.pitch_scatter.clear();
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+    ) -> GameResult<()> {
+        let grid = (2, 2);
+
+        //axles, relative to slow moving static height, so only dynamics are visible
+        let mut relative = self.travel.clone();
+        if let Some(ref baseline) = self.baseline {
+            for (r, b) in relative.data.iter_mut().zip(baseline.data.iter()) {
+                *r -= *b;
+            }
+        }
+
+        let width = screen_size.x * 0.6 * 0.5 * 0.3;
+        let (front, rear) = linearize(&relative, width, 0f32, 3000f32);
+
+        for (axle, name, cell) in [(front, "Front", (0, 0)), (rear, "Rear", (0, 1))].iter() {
+            let center = scale_left_cell(0.5, 0.5, *cell, grid, screen_size);
+            let points: Vec<Point2> = axle
+                .iter()
+                .map(|p| Point2::new(center.x + p.x, center.y + p.y))
+                .collect();
+
+            graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+            graphics::line(
+                ctx,
+                &[
+                    Point2::new(center.x - width, center.y),
+                    Point2::new(center.x + width, center.y),
+                ],
+                1f32,
+            )?;
+
+            graphics::set_color(ctx, Color::from_rgb(173, 255, 47))?;
+            graphics::line(ctx, &points, 3f32)?;
+            for p in points.iter() {
+                graphics::circle(ctx, DrawMode::Fill, *p, 4f32, 1f32)?;
+            }
+
+            graphics::set_color(ctx, WHITE)?;
+            let dest = scale_left_cell(0f32, 1.1, *cell, grid, screen_size);
+            labels.draw(ctx, name, dest)?;
+        }
+
+        let reference = self.reference.clone();
+
+        draw_scatter(
+            ctx,
+            screen_size,
+            labels,
+            (1, 0),
+            &self.roll_scatter,
+            &self.roll_fit,
+            (3f32, 4f32),
+            "Roll",
+            "lateral g",
+            reference.and_then(|r| r.0),
+        )?;
+
+        draw_scatter(
+            ctx,
+            screen_size,
+            labels,
+            (1, 1),
+            &self.pitch_scatter,
+            &self.pitch_fit,
+            (3f32, 3f32),
+            "Pitch",
+            "braking g",
+            reference.and_then(|r| r.1),
+        )?;
+
+        Ok(())
+    }
+}
+
+fn roll_angle(travel: &TyresArray<f32>) -> f32 {
+    let front =
+        travel.data[Tyre::TyreFrontLeft as usize] - travel.data[Tyre::TyreFrontRight as usize];
+    let rear = travel.data[Tyre::TyreRearLeft as usize] - travel.data[Tyre::TyreRearRight as usize];
+
+    ((front + rear) / 2f32 / NOMINAL_TRACK_WIDTH)
+        .atan()
+        .to_degrees()
+}
+
+fn pitch_angle(travel: &TyresArray<f32>) -> f32 {
+    ((travel.front_avg() - travel.rear_avg()) / NOMINAL_WHEELBASE)
+        .atan()
+        .to_degrees()
+}
+
+fn push_limited(deque: &mut VecDeque<(f32, f32)>, value: (f32, f32)) {
+    if deque.len() >= SCATTER_LENGTH {
+        deque.pop_front();
+    }
+    deque.push_back(value);
+}
+
+fn draw_scatter(
+    ctx: &mut Context,
+    screen_size: &Point2,
+    labels: &mut TextCache,
+    cell: (usize, usize),
+    scatter: &VecDeque<(f32, f32)>,
+    fit: &LinearFit,
+    range: (f32, f32),
+    name: &str,
+    axis: &str,
+    reference: Option<f32>,
+) -> GameResult<()> {
+    let grid = (2, 2);
+    let (x_range, y_range) = range;
+    let to_screen = |x: f32, y: f32| -> Point2 {
+        scale_left_cell(
+            (x / x_range + 1f32) / 2f32,
+            ((y / y_range + 1f32) / 2f32).max(0f32).min(1f32),
+            cell,
+            grid,
+            screen_size,
+        )
+    };
+
+    graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+    graphics::line(
+        ctx,
+        &[to_screen(-x_range, 0f32), to_screen(x_range, 0f32)],
+        1f32,
+    )?;
+    graphics::line(
+        ctx,
+        &[to_screen(0f32, -y_range), to_screen(0f32, y_range)],
+        1f32,
+    )?;
+
+    if !scatter.is_empty() {
+        let points: Vec<Point2> = scatter.iter().map(|(x, y)| to_screen(*x, *y)).collect();
+        graphics::set_color(ctx, Color::from_rgba(0, 191, 255, 160))?;
+        graphics::points(ctx, &points, 2f32)?;
+    }
+
+    if let (Some(slope), Some(intercept)) = (fit.slope(), fit.intercept()) {
+        graphics::set_color(ctx, Color::from_rgb(255, 140, 0))?;
+        graphics::line(
+            ctx,
+            &[
+                to_screen(-x_range, intercept - slope * x_range),
+                to_screen(x_range, intercept + slope * x_range),
+            ],
+            2f32,
+        )?;
+    }
+
+    let text = match (fit.slope(), reference) {
+        (Some(slope), Some(reference)) => format!(
+            "{}: {:.2} deg/g (was {:.2}) vs {}",
+            name,
+            slope.abs(),
+            reference,
+            axis
+        ),
+        (Some(slope), None) => format!("{}: {:.2} deg/g vs {}", name, slope.abs(), axis),
+        _ => format!("{}: not enough data", name),
+    };
+
+    graphics::set_color(ctx, WHITE)?;
+    let dest = scale_left_cell(0f32, 1.1, cell, grid, screen_size);
+    labels.draw(ctx, &text, dest)?;
+
+    Ok(())
+}
+
+const DIFF_ROWS: [&str; 11] = [
+    "Entry (turning in)",
+    "Apex (at peak g)",
+    "Exit (unwinding)",
+    "Throttle 0-25%",
+    "Throttle 25-50%",
+    "Throttle 50-75%",
+    "Throttle 75-100%",
+    "Brake 0-25%",
+    "Brake 25-50%",
+    "Brake 50-75%",
+    "Brake 75-100%",
+];
+
+const ENTRY: usize = 0;
+const APEX: usize = 1;
+const EXIT: usize = 2;
+const THROTTLE: usize = 3;
+const BRAKE: usize = 7;
+
+//fast and slow average smoothing, their gap tells if a value is building or releasing
+const TREND_FAST: f32 = 0.3;
+const TREND_SLOW: f32 = 0.05;
+//gap that counts as building or releasing, g and steering input
+const LATERAL_TREND: f32 = 0.05;
+const STEERING_TREND: f32 = 0.02;
+
+/// Fast average against slow one, positive while value is building.
+#[derive(Default)]
+struct Trend {
+    fast: f32,
+    slow: f32,
+    started: bool,
+}
+
+impl Trend {
+    fn add(&mut self, value: f32) -> f32 {
+        if !self.started {
+            self.fast = value;
+            self.slow = value;
+            self.started = true;
+        }
+        self.fast += (value - self.fast) * TREND_FAST;
+        self.slow += (value - self.slow) * TREND_SLOW;
+        self.fast - self.slow
+    }
+
+    /// 1 building, -1 releasing, 0 steady.
+    fn direction(&mut self, value: f32, limit: f32) -> i32 {
+        let gap = self.add(value);
+        if gap > limit {
+            1
+        } else if gap < -limit {
+            -1
+        } else {
+            0
+        }
+    }
+}
+
+/// Inner to outer wheel speed ratio in corners, 1.0 is a locked axle,
+/// below that inner wheel is slower, above - inner wheel spins up.
+pub struct DiffGraphData {
+    front: [Average; 11],
+    rear: [Average; 11],
+    lateral: Trend,
+    steering: Trend,
+}
+
+impl DiffGraphData {
+    pub fn new() -> DiffGraphData {
+        DiffGraphData {
+            front: [Average::default(); 11],
+            rear: [Average::default(); 11],
+            lateral: Trend::default(),
+            steering: Trend::default(),
+        }
+    }
+
+    pub fn add(&mut self, tyre_rps: &TyresArray<f32>, lateral: f32, inputs: &Inputs) {
+        //straights tell nothing about the diff
+        if lateral.abs() < 0.3 || inputs.steering.abs() < 0.05 {
+            self.lateral = Trend::default();
+            self.steering = Trend::default();
+            return;
+        }
+
+        let rps = |tyre: Tyre| tyre_rps.data[tyre as usize].abs();
+
+        //acceleration points inside the corner, negative is left, steering may be countersteer
+        let left_inner = lateral < 0f32;
+        let axle_ratio = |left: f32, right: f32| -> Option<f32> {
+            let (inner, outer) = if left_inner {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            if inner < 1f32 || outer < 1f32 {
+                None
+            } else {
+                Some(inner / outer)
+            }
+        };
+
+        let front = axle_ratio(rps(Tyre::TyreFrontLeft), rps(Tyre::TyreFrontRight));
+        let rear = axle_ratio(rps(Tyre::TyreRearLeft), rps(Tyre::TyreRearRight));
+
+        //turning in builds steering and g, apex holds them, exit releases them
+        let lateral = self.lateral.direction(lateral.abs(), LATERAL_TREND);
+        let steering = self.steering.direction(inputs.steering.abs(), STEERING_TREND);
+        let phase = if lateral >= 0 && steering >= 0 && lateral + steering > 0 {
+            ENTRY
+        } else if lateral <= 0 && steering <= 0 && lateral + steering < 0 {
+            EXIT
+        } else {
+            APEX
+        };
+
+        let mut rows: SmallVec<[usize; 3]> = SmallVec::new();
+        rows.push(phase);
+        if inputs.brake > 0.05 {
+            rows.push(BRAKE + bucket(inputs.brake));
+        } else {
+            rows.push(THROTTLE + bucket(inputs.throttle));
+        }
+
+        for row in rows.iter() {
+            if let Some(front) = front {
+                self.front[*row].add(front);
+            }
+            if let Some(rear) = rear {
+                self.rear[*row].add(rear);
+            }
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+        numeric_cache: &NumericTextCache,
+        ratios: &BTreeMap<i32, Ratio>,
+    ) -> GameResult<()> {
+        let row_height = 1f32 / (DIFF_ROWS.len() + 2) as f32;
+        let bars_start = 0.2;
+        let bars_width = 0.65;
+
+        //ratios go from 0.8 to 1.2, locked is in the middle
+        let to_x = |ratio: f32| -> f32 {
+            bars_start + bars_width * ((ratio - 0.8) / 0.4).max(0f32).min(1f32)
+        };
 
-pub struct RollGraphData;
+        graphics::set_color(ctx, Color::from_rgba(127, 127, 127, 127))?;
+        graphics::line(
+            ctx,
+            &[
+                scale_left(to_x(1f32), 1f32 - row_height, screen_size),
+                scale_left(to_x(1f32), row_height, screen_size),
+            ],
+            1f32,
+        )?;
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(
+            ctx,
+            "inner/outer wheel speed, %: front (blue), rear (green)",
+            scale_left(bars_start, 1f32 - row_height * 0.2, screen_size),
+        )?;
+
+        for (ix, name) in DIFF_ROWS.iter().enumerate() {
+            let y = 1f32 - row_height * (ix + 1) as f32;
+
+            graphics::set_color(ctx, WHITE)?;
+            labels.draw(ctx, name, scale_left(0.01, y, screen_size))?;
+
+            let axles = [
+                (self.front[ix].value(), Color::from_rgb(0, 191, 255), 0f32),
+                (self.rear[ix].value(), Color::from_rgb(34, 177, 76), 0.4),
+            ];
+
+            for (value, color, offset) in axles.iter() {
+                if let Some(ratio) = value {
+                    let top = y - row_height * offset;
+                    let start = scale_left(to_x(1f32), top, screen_size);
+                    let end = scale_left(to_x(*ratio), top - row_height * 0.35, screen_size);
+
+                    graphics::set_color(ctx, *color)?;
+                    graphics::rectangle(
+                        ctx,
+                        DrawMode::Fill,
+                        Rect::new(
+                            start.x.min(end.x),
+                            start.y,
+                            (end.x - start.x).abs().max(1f32),
+                            end.y - start.y,
+                        ),
+                    )?;
+
+                    let percent = (ratio * 100f32).round() as i32;
+                    if let Some(text) = numeric_cache.small_numbers.get(&percent) {
+                        let dest = scale_left(bars_start + bars_width + 0.02, top, screen_size);
+                        graphics::draw(ctx, text, dest, 0f32)?;
+                    }
+                }
+            }
+        }
+
+        //what gears module saw on throttle, rear only
+        let mut dest = scale_left(0.01, row_height * 0.8, screen_size);
+        graphics::set_color(ctx, WHITE)?;
+        dest.x += labels.draw(ctx, "Rear lock on throttle by gear, %:", dest)? + 6f32;
+        for (gear, ratio) in ratios.iter() {
+            dest.x += labels.draw(ctx, &format!("{}: ", gear), dest)?;
+            let percent = (ratio.differential * 100f32).round() as i32;
+            if let Some(text) = numeric_cache.small_numbers.get(&percent) {
+                graphics::draw(ctx, text, dest, 0f32)?;
+                dest.x += text.width() as f32 + 6f32;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn bucket(pedal: f32) -> usize {
+    ((pedal * 4f32) as usize).min(3)
+}
 
-fn _linearize(
+fn linearize(
     tyre_array: &TyresArray<f32>,
     width: f32,
     height: f32,
@@ -0,0 +1,267 @@
+use definitions::*;
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use super::Inputs;
+use std::collections::VecDeque;
+use std::f32;
+use util::*;
+
+//slip ratio thresholds, wheel surface speed against car speed
+const SPIN_SLIP: f32 = 0.15;
+const LOCK_SLIP: f32 = -0.25;
+//m/s, used when rolling radius is not known yet
+const SLIP_SPEED: f32 = 3f32;
+const MIN_SPEED: f32 = 5f32;
+const EVENTS_MAX: usize = 200;
+//meters slip has to last, whatever the frame rate, single frame spikes are noise
+const MIN_LENGTH: f32 = 2f32;
+
+const TYRE_NAMES: [&str; 4] = ["FL", "FR", "RL", "RR"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlipKind {
+    Wheelspin,
+    Lockup,
+}
+
+#[derive(Debug, Clone)]
+pub struct SlipEvent {
+    pub kind: SlipKind,
+    pub tyre: usize,
+    pub lap: i32,
+    pub start: f32,
+    pub end: f32,
+    pub peak: f32,
+}
+
+impl SlipEvent {
+    fn lasted(&self) -> bool {
+        self.end - self.start >= MIN_LENGTH
+    }
+}
+
+pub struct SlipGraphData {
+    radius: [Option<f32>; 4],
+    active: [Option<SlipEvent>; 4],
+    pub events: VecDeque<SlipEvent>,
+    current_lap: i32,
+}
+
+impl SlipGraphData {
+    pub fn new() -> SlipGraphData {
+        SlipGraphData {
+            radius: [None; 4],
+            active: [None, None, None, None],
+            events: VecDeque::new(),
+            current_lap: 0,
+        }
+    }
+
+    pub fn add(
+        &mut self,
+        lap: i32,
+        distance: f32,
+        speed: f32,
+        tyre_rps: &TyresArray<f32>,
+        slip_speed: &TyresArray<f32>,
+        abs_active: bool,
+        inputs: &Inputs,
+    ) {
+        if lap != self.current_lap {
+            for ix in 0..TYRE_MAX {
+                self.finish(ix);
+            }
+            self.current_lap = lap;
+        }
+
+        if speed < MIN_SPEED {
+            return;
+        }
+
+        let coasting =
+            inputs.throttle < 0.05 && inputs.brake < 0.05 && inputs.steering.abs() < 0.05;
+
+        for ix in 0..TYRE_MAX {
+            let rps = tyre_rps.data[ix].abs();
+
+            //free rolling wheels give away their radius
+            if coasting && rps > 1f32 {
+                let radius = speed / (rps * 2f32 * f32::consts::PI);
+                let estimate = self.radius[ix].unwrap_or(radius);
+                self.radius[ix] = Some(estimate + (radius - estimate) * 0.01);
+            }
+
+            let slip = match self.radius[ix] {
+                Some(radius) => (rps * 2f32 * f32::consts::PI * radius - speed) / speed,
+                None if slip_speed.data[ix].abs() > SLIP_SPEED => {
+                    let sign = if inputs.brake > inputs.throttle {
+                        -1f32
+                    } else {
+                        1f32
+                    };
+                    sign * slip_speed.data[ix].abs() / speed
+                }
+                None => 0f32,
+            };
+
+            let kind = if slip > SPIN_SLIP && inputs.throttle > 0.3 {
+                Some(SlipKind::Wheelspin)
+            } else if slip < LOCK_SLIP && inputs.brake > 0.2 && !abs_active {
+                //ABS doing its job is not a lockup
+                Some(SlipKind::Lockup)
+            } else {
+                None
+            };
+
+            let same_kind = self.active[ix].as_ref().map(|e| Some(e.kind) == kind);
+
+            match (kind, same_kind) {
+                (Some(_), Some(true)) => {
+                    if let Some(ref mut event) = self.active[ix] {
+                        event.end = distance;
+                        event.peak = event.peak.max(slip.abs());
+                    }
+                }
+                (Some(kind), _) => {
+                    self.finish(ix);
+                    self.active[ix] = Some(SlipEvent {
+                        kind,
+                        tyre: ix,
+                        lap,
+                        start: distance,
+                        end: distance,
+                        peak: slip.abs(),
+                    });
+                }
+                (None, _) => self.finish(ix),
+            }
+        }
+    }
+
+    fn finish(&mut self, ix: usize) {
+        if let Some(event) = self.active[ix].take() {
+            if !event.lasted() {
+                return;
+            }
+            if self.events.len() >= EVENTS_MAX {
+                self.events.pop_front();
+            }
+            self.events.push_back(event);
+        }
+    }
+
+    /// Ticks along the bottom of distance based left panel, current lap is bright, previous one faded.
+    pub fn draw_markers(
+        &self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        track_length: f32,
+    ) -> GameResult<()> {
+        let visible = self
+            .events
+            .iter()
+            .chain(
+                self.active
+                    .iter()
+                    .filter_map(|e| e.as_ref())
+                    .filter(|e| e.lasted()),
+            )
+            .filter(|e| e.lap == self.current_lap || e.lap == self.current_lap - 1);
+
+        for event in visible {
+            let mut color = match event.kind {
+                SlipKind::Wheelspin => Color::from_rgb(255, 201, 14),
+                SlipKind::Lockup => Color::from_rgb(250, 0, 0),
+            };
+            if event.lap != self.current_lap {
+                color.a = 0.35;
+            }
+
+            //front tyres above rear ones
+            let row = if event.tyre < 2 { 0.04 } else { 0.01 };
+            let start = scale_left(event.start / track_length, row + 0.025, screen_size);
+            let end = scale_left(event.end / track_length, row, screen_size);
+
+            graphics::set_color(ctx, color)?;
+            graphics::rectangle(
+                ctx,
+                DrawMode::Fill,
+                Rect::new(
+                    start.x,
+                    start.y,
+                    (end.x - start.x).max(2f32),
+                    end.y - start.y,
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn draw_list(
+        &self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+    ) -> GameResult<()> {
+        let lines = 36;
+        let line_height = 1f32 / (lines + 1) as f32;
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(
+            ctx,
+            "Wheelspin and lockups, newest first",
+            scale_left(0.01, 1f32 - line_height * 0.2, screen_size),
+        )?;
+
+        if self.events.is_empty() {
+            labels.draw(
+                ctx,
+                "Nothing yet",
+                scale_left(0.01, 1f32 - line_height * 1.2, screen_size),
+            )?;
+        }
+
+        let mut last_lap = None;
+        for (n, event) in self.events.iter().rev().take(lines).enumerate() {
+            let y = 1f32 - line_height * (n + 1) as f32 - line_height * 0.2;
+
+            let color = match event.kind {
+                SlipKind::Wheelspin => Color::from_rgb(255, 201, 14),
+                SlipKind::Lockup => Color::from_rgb(250, 0, 0),
+            };
+
+            let lap = if last_lap != Some(event.lap) {
+                last_lap = Some(event.lap);
+                format!("Lap {}", event.lap)
+            } else {
+                String::new()
+            };
+
+            let kind = match event.kind {
+                SlipKind::Wheelspin => "wheelspin",
+                SlipKind::Lockup => "lockup",
+            };
+
+            let line = format!(
+                "{} {} at {}m for {}m, peak slip {}%",
+                TYRE_NAMES[event.tyre],
+                kind,
+                event.start as i32,
+                (event.end - event.start).max(0f32) as i32,
+                (event.peak * 100f32) as i32,
+            );
+
+            graphics::set_color(ctx, WHITE)?;
+            if !lap.is_empty() {
+                labels.draw(ctx, &lap, scale_left(0.01, y, screen_size))?;
+            }
+            graphics::set_color(ctx, color)?;
+            labels.draw(ctx, &line, scale_left(0.08, y, screen_size))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+use decode::*;
+use definitions::*;
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use std::collections::VecDeque;
+use std::time::Duration;
+use util::*;
+
+//meters between trail samples
+const TRAIL_STEP: f32 = 10f32;
+//laps of trail kept per car, enough to time the gap to a lapped car
+const TRAIL_LAPS: f32 = 2f32;
+const ROWS: usize = 32;
+
+const FLAG_NAMES: [&str; 12] = [
+    "",
+    "green",
+    "blue",
+    "slow car",
+    "final lap",
+    "RED",
+    "yellow",
+    "double yellow",
+    "b/w",
+    "mechanical",
+    "BLACK",
+    "chequered",
+];
+const PIT_NAMES: [&str; 6] = ["", "pit in", "in pit", "pit out", "garage", "garage out"];
+
+/// One participant, as of latest update.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub index: usize,
+    pub name: String,
+    pub class: String,
+    pub position: u32,
+    pub laps_completed: u32,
+    pub lap_distance: f32,
+    /// Distance covered in the session, ranks cars on track regardless of reported position.
+    pub total: f32,
+    pub speed: f32,
+    pub last_lap: f32,
+    pub best_lap: f32,
+    pub sector: i32,
+    pub pit_mode: u32,
+    pub flag: u32,
+    pub world: Vec3<f32>,
+}
+
+impl Entry {
+    pub fn in_pits(&self) -> bool {
+        self.pit_mode != 0
+    }
+}
+
+pub struct StandingsData {
+    pub entries: Vec<Entry>,
+    pub viewed: Option<usize>,
+    pub track_length: f32,
+    trails: Vec<VecDeque<(f32, Duration)>>,
+    now: Duration,
+}
+
+impl StandingsData {
+    pub fn new(track_length: f32) -> StandingsData {
+        StandingsData {
+            entries: Vec::new(),
+            viewed: None,
+            track_length: track_length.max(1f32),
+            trails: (0..STORED_PARTICIPANTS_MAX)
+                .map(|_| VecDeque::new())
+                .collect(),
+            now: Duration::from_secs(0),
+        }
+    }
+
+    pub fn update(&mut self, sm: &SharedMemory, time: Duration) {
+        self.now = time;
+        self.entries.clear();
+
+        let trail_max = (self.track_length * TRAIL_LAPS / TRAIL_STEP) as usize + 1;
+
+        for ix in 0..sm.participant_count() {
+            let info = &sm.mParticipantInfo.data[ix];
+            if !info.is_active() {
+                continue;
+            }
+
+            let total = info.mLapsCompleted as f32 * self.track_length + info.mCurrentLapDistance;
+
+            let trail = &mut self.trails[ix];
+            let last = trail.back().map(|s| s.0);
+            match last {
+                //slot reused or car reset to pits
+                Some(last) if total < last - 100f32 => {
+                    trail.clear();
+                    trail.push_back((total, time));
+                }
+                Some(last) if total - last < TRAIL_STEP => {}
+                _ => trail.push_back((total, time)),
+            }
+            while trail.len() > trail_max {
+                trail.pop_front();
+            }
+
+            self.entries.push(Entry {
+                index: ix,
+                name: display_name(&info.mName),
+                class: display_name(&sm.mCarClassNames.data[ix]),
+                position: info.mRacePosition,
+                laps_completed: info.mLapsCompleted,
+                lap_distance: info.mCurrentLapDistance,
+                total,
+                speed: sm.mSpeeds.data[ix],
+                last_lap: sm.mLastLapTimes.data[ix],
+                best_lap: sm.mFastestLapTimes.data[ix],
+                sector: info.mCurrentSector,
+                pit_mode: sm.mPitModes.data[ix],
+                flag: sm.mHighestFlagColours.data[ix],
+                world: info.mWorldPosition,
+            });
+        }
+
+        //unset positions go last
+        self.entries.sort_by_key(|e| {
+            if e.position == 0 {
+                u32::max_value()
+            } else {
+                e.position
+            }
+        });
+
+        self.viewed = match sm.viewed_participant() {
+            Some(viewed) => self.entries.iter().position(|e| e.index == viewed),
+            None => None,
+        };
+    }
+
+    pub fn viewed_entry(&self) -> Option<&Entry> {
+        self.viewed.map(|ix| &self.entries[ix])
+    }
+
+    /// Seconds since car ahead was where car behind is now.
+    pub fn time_gap(&self, behind: &Entry, ahead: &Entry) -> Option<f32> {
+        let trail = &self.trails[ahead.index];
+        let target = behind.total;
+
+        let after = trail.iter().position(|s| s.0 >= target)?;
+        if after == 0 {
+            return None;
+        }
+
+        let (d1, t1) = trail[after - 1];
+        let (d2, t2) = trail[after];
+        let t1 = seconds(t1);
+        let t2 = seconds(t2);
+        let share = if d2 > d1 {
+            (target - d1) / (d2 - d1)
+        } else {
+            0f32
+        };
+
+        Some(seconds(self.now) - (t1 + (t2 - t1) * share))
+    }
+
+    /// Time gap, or whole laps when car behind is lapped.
+    pub fn format_gap(&self, behind: &Entry, ahead: &Entry) -> String {
+        let laps = ((ahead.total - behind.total) / self.track_length) as i32;
+        if laps >= 1 {
+            format!("+{}L", laps)
+        } else {
+            self.time_gap(behind, ahead)
+                .map(|gap| format!("+{:.1}", gap))
+                .unwrap_or_else(|| "-".to_string())
+        }
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+    ) -> GameResult<()> {
+        let line_height = 1f32 / (ROWS + 2) as f32;
+        let columns = [0.01, 0.05, 0.16, 0.38, 0.47, 0.56, 0.67, 0.78, 0.87];
+        let header = [
+            "Pos", "Class", "Name", "Leader", "Interval", "Last", "Best", "Pit", "Flag",
+        ];
+
+        graphics::set_color(ctx, WHITE)?;
+        for (x, title) in columns.iter().zip(header.iter()) {
+            labels.draw(ctx, title, scale_left(*x, 1f32, screen_size))?;
+        }
+
+        let leader = match self.entries.first() {
+            Some(leader) => leader,
+            None => {
+                labels.draw(
+                    ctx,
+                    "No participants",
+                    scale_left(0.01, 1f32 - line_height, screen_size),
+                )?;
+                return Ok(());
+            }
+        };
+
+        for (n, entry) in self.entries.iter().take(ROWS).enumerate() {
+            let y = 1f32 - line_height * (n + 1) as f32;
+
+            let (to_leader, interval) = if n == 0 {
+                (String::new(), String::new())
+            } else {
+                (
+                    self.format_gap(entry, leader),
+                    self.format_gap(entry, &self.entries[n - 1]),
+                )
+            };
+
+            let cells = [
+                format!("{}", entry.position),
+                entry.class.clone(),
+                entry.name.clone(),
+                to_leader,
+                interval,
+                format_lap(entry.last_lap),
+                format_lap(entry.best_lap),
+                PIT_NAMES
+                    .get(entry.pit_mode as usize)
+                    .cloned()
+                    .unwrap_or("")
+                    .to_string(),
+                FLAG_NAMES
+                    .get(entry.flag as usize)
+                    .cloned()
+                    .unwrap_or("")
+                    .to_string(),
+            ];
+
+            let color = if Some(n) == self.viewed {
+                Color::from_rgb(255, 201, 14)
+            } else if entry.in_pits() {
+                Color::from_rgb(127, 127, 127)
+            } else {
+                WHITE
+            };
+
+            graphics::set_color(ctx, color)?;
+            for (x, cell) in columns.iter().zip(cells.iter()) {
+                if !cell.is_empty() {
+                    labels.draw(ctx, cell, scale_left(*x, y, screen_size))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn seconds(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_millis() as f32 / 1000f32
+}
+
+/// Minutes, seconds and milliseconds, dash for unset times.
+pub fn format_lap(time: f32) -> String {
+    if time <= 0f32 {
+        "-".to_string()
+    } else {
+        let minutes = (time / 60f32) as i32;
+        format!("{}:{:06.3}", minutes, time - minutes as f32 * 60f32)
+    }
+}
@@ -0,0 +1,353 @@
+use ggez::graphics::*;
+use ggez::*;
+
+use super::nets::*;
+use std::collections::VecDeque;
+use util::*;
+
+//clean laps in rolling average
+const FUEL_WINDOW: usize = 5;
+const LAPS_KEPT: usize = 30;
+//extra laps of fuel to carry to the flag
+const SAFETY_LAPS: f32 = 1f32;
+
+pub struct FuelLap {
+    pub lap: i32,
+    pub used: f32,
+    pub time: f32,
+    /// Pit lane, refuel or invalid lap, kept in the list but not in the average.
+    pub clean: bool,
+}
+
+/// Everything known about the fuel left and needed, liters.
+pub struct Strategy {
+    pub fuel: f32,
+    pub per_lap: f32,
+    pub laps_of_fuel: f32,
+    pub laps_to_go: Option<f32>,
+    pub fuel_needed: Option<f32>,
+    pub stops: Option<u32>,
+    pub pit_before_lap: Option<i32>,
+    pub add_at_stop: Option<f32>,
+}
+
+pub struct StrategyData {
+    pub laps: VecDeque<FuelLap>,
+    current_lap: i32,
+    lap_start_fuel: Option<f32>,
+    last_fuel: f32,
+    dirty: bool,
+    capacity: f32,
+}
+
+impl StrategyData {
+    pub fn new() -> StrategyData {
+        StrategyData {
+            laps: VecDeque::new(),
+            current_lap: 0,
+            lap_start_fuel: None,
+            last_fuel: 0f32,
+            dirty: true,
+            capacity: 0f32,
+        }
+    }
+
+    /// Fuel level is a share of capacity as game gives it, capacity in liters.
+    pub fn add(
+        &mut self,
+        lap: i32,
+        fuel_level: f32,
+        capacity: f32,
+        last_lap_time: f32,
+        in_pits: bool,
+        lap_invalidated: bool,
+    ) {
+        let fuel = fuel_level * capacity;
+        self.capacity = capacity;
+
+        if lap != self.current_lap {
+            if let Some(start) = self.lap_start_fuel {
+                if self.laps.len() >= LAPS_KEPT {
+                    self.laps.pop_front();
+                }
+                self.laps.push_back(FuelLap {
+                    lap: self.current_lap,
+                    used: start - fuel,
+                    time: last_lap_time,
+                    clean: !self.dirty && start > fuel && last_lap_time > 0f32,
+                });
+            }
+
+            //started mid lap, fuel at its start is not known
+            self.dirty = in_pits || self.lap_start_fuel.is_none();
+            self.current_lap = lap;
+            self.lap_start_fuel = Some(fuel);
+        }
+
+        //refueled or reset to pits
+        if fuel > self.last_fuel + 0.1 {
+            self.dirty = true;
+        }
+        self.dirty = self.dirty || in_pits || lap_invalidated;
+        self.last_fuel = fuel;
+    }
+
+    fn clean_laps(&self) -> Vec<&FuelLap> {
+        self.laps
+            .iter()
+            .rev()
+            .filter(|l| l.clean)
+            .take(FUEL_WINDOW)
+            .collect()
+    }
+
+    pub fn per_lap(&self) -> Option<f32> {
+        let laps = self.clean_laps();
+        if laps.is_empty() {
+            None
+        } else {
+            Some(laps.iter().map(|l| l.used).sum::<f32>() / laps.len() as f32)
+        }
+    }
+
+    pub fn lap_time(&self) -> Option<f32> {
+        let laps = self.clean_laps();
+        if laps.is_empty() {
+            None
+        } else {
+            Some(laps.iter().map(|l| l.time).sum::<f32>() / laps.len() as f32)
+        }
+    }
+
+    /// Laps in event wins over time remaining, time remaining in seconds,
+    /// lap fraction is how much of current lap is done.
+    pub fn strategy(
+        &self,
+        laps_in_event: u32,
+        laps_completed: u32,
+        time_remaining: f32,
+        lap_fraction: f32,
+    ) -> Option<Strategy> {
+        let per_lap = self.per_lap()?;
+        let fuel = self.last_fuel;
+        let lap_fraction = lap_fraction.max(0f32).min(1f32);
+
+        let laps_to_go = if laps_in_event > 0 {
+            Some((laps_in_event as f32 - laps_completed as f32 - lap_fraction).max(0f32))
+        } else if time_remaining > 0f32 {
+            //race ends when line is crossed after the clock runs out
+            self.lap_time()
+                .map(|lap_time| (lap_fraction + time_remaining / lap_time).ceil() - lap_fraction)
+        } else {
+            None
+        };
+
+        let laps_of_fuel = fuel / per_lap;
+        let fuel_needed = laps_to_go.map(|laps| (laps + SAFETY_LAPS) * per_lap);
+
+        let (stops, pit_before_lap, add_at_stop) = match fuel_needed {
+            Some(needed) if needed > fuel && self.capacity > 0f32 => {
+                let missing = needed - fuel;
+                let stops = (missing / self.capacity).ceil() as u32;
+                let pit_lap = self.current_lap + (lap_fraction + laps_of_fuel).floor() as i32;
+                (Some(stops), Some(pit_lap), Some(missing.min(self.capacity)))
+            }
+            Some(_) => (Some(0), None, None),
+            None => (None, None, None),
+        };
+
+        Some(Strategy {
+            fuel,
+            per_lap,
+            laps_of_fuel,
+            laps_to_go,
+            fuel_needed,
+            stops,
+            pit_before_lap,
+            add_at_stop,
+        })
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        screen_size: &Point2,
+        labels: &mut TextCache,
+        strategy: Option<Strategy>,
+    ) -> GameResult<()> {
+        let grid = (1, 2);
+        let line_height = 0.1;
+
+        let format_opt = |value: Option<f32>, unit: &str| -> String {
+            value
+                .map(|v| format!("{:.1}{}", v, unit))
+                .unwrap_or_else(|| "-".to_string())
+        };
+
+        let mut lines = vec![format!(
+            "Fuel: {:.1}L of {:.0}L",
+            self.last_fuel, self.capacity
+        )];
+
+        match strategy {
+            Some(ref s) => {
+                lines.push(format!(
+                    "Per lap: {:.2}L (last {} clean laps), enough for {:.1} laps",
+                    s.per_lap,
+                    self.clean_laps().len(),
+                    s.laps_of_fuel
+                ));
+                lines.push(format!(
+                    "Laps to go: {}, needed to finish: {} with {} lap margin",
+                    format_opt(s.laps_to_go, ""),
+                    format_opt(s.fuel_needed, "L"),
+                    SAFETY_LAPS
+                ));
+                lines.push(match (s.stops, s.pit_before_lap, s.add_at_stop) {
+                    (Some(stops), Some(lap), Some(add)) => format!(
+                        "Pit: {} stop(s), before lap {}, add {:.1}L",
+                        stops, lap, add
+                    ),
+                    (Some(_), _, _) => "No stop needed".to_string(),
+                    _ => "Race length unknown".to_string(),
+                });
+            }
+            None => lines.push("Waiting for a clean lap".to_string()),
+        }
+
+        for (n, line) in lines.iter().enumerate() {
+            let color = if n == 3 {
+                Color::from_rgb(255, 201, 14)
+            } else {
+                WHITE
+            };
+            graphics::set_color(ctx, color)?;
+            labels.draw(
+                ctx,
+                line,
+                scale_left_cell(
+                    0f32,
+                    1f32 - line_height * n as f32,
+                    (0, 0),
+                    grid,
+                    screen_size,
+                ),
+            )?;
+        }
+
+        //fuel per lap bars, faded ones are not in the average
+        let max_used = self
+            .laps
+            .iter()
+            .map(|l| l.used)
+            .fold(0.1f32, |acc, u| acc.max(u))
+            * 1.2;
+        let bar = 1f32 / LAPS_KEPT as f32;
+
+        for (n, lap) in self.laps.iter().enumerate() {
+            let mut color = Color::from_rgb(0, 191, 255);
+            if !lap.clean {
+                color.a = 0.3;
+            }
+
+            let height = (lap.used / max_used).max(0f32).min(1f32);
+            let top_left = scale_left_cell(bar * n as f32, height, (0, 1), grid, screen_size);
+            let bottom_right =
+                scale_left_cell(bar * (n as f32 + 0.8), 0f32, (0, 1), grid, screen_size);
+
+            graphics::set_color(ctx, color)?;
+            graphics::rectangle(
+                ctx,
+                DrawMode::Fill,
+                Rect::new(
+                    top_left.x,
+                    top_left.y,
+                    bottom_right.x - top_left.x,
+                    bottom_right.y - top_left.y,
+                ),
+            )?;
+
+            graphics::set_color(ctx, WHITE)?;
+            labels.draw(
+                ctx,
+                &format!("{}", lap.lap),
+                scale_left_cell(bar * n as f32, -0.05, (0, 1), grid, screen_size),
+            )?;
+        }
+
+        if let Some(per_lap) = self.per_lap() {
+            let y = per_lap / max_used;
+            graphics::set_color(ctx, Color::from_rgb(255, 201, 14))?;
+            graphics::line(
+                ctx,
+                &[
+                    scale_left_cell(0f32, y, (0, 1), grid, screen_size),
+                    scale_left_cell(1f32, y, (0, 1), grid, screen_size),
+                ],
+                1f32,
+            )?;
+        }
+
+        graphics::set_color(ctx, WHITE)?;
+        labels.draw(
+            ctx,
+            &format!(
+                "Fuel used per lap, up to {:.1}L, yellow: rolling average",
+                max_used
+            ),
+            scale_left_cell(0f32, 1.1, (0, 1), grid, screen_size),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAPACITY: f32 = 100f32;
+
+    //one call at lap start and one mid lap, 2L used per lap
+    fn drive(data: &mut StrategyData, laps: ::std::ops::Range<i32>, mut fuel: f32) -> f32 {
+        for lap in laps {
+            data.add(lap, fuel / CAPACITY, CAPACITY, 90f32, false, false);
+            fuel -= 1f32;
+            data.add(lap, fuel / CAPACITY, CAPACITY, 90f32, false, false);
+            fuel -= 1f32;
+        }
+        fuel
+    }
+
+    #[test]
+    fn lap_joined_mid_way_is_not_clean() {
+        let mut data = StrategyData::new();
+        //joined halfway through lap 3, only 1L of it seen
+        data.add(3, 0.5, CAPACITY, 0f32, false, false);
+        let fuel = 49f32;
+        drive(&mut data, 4..7, fuel);
+
+        let lap_3 = data.laps.iter().find(|l| l.lap == 3).unwrap();
+        assert!(!lap_3.clean);
+        assert_eq!(data.clean_laps().len(), 2);
+        assert!((data.per_lap().unwrap() - 2f32).abs() < 0.001);
+    }
+
+    #[test]
+    fn refuel_and_pit_laps_are_not_clean() {
+        let mut data = StrategyData::new();
+        let fuel = drive(&mut data, 1..4, 60f32);
+        data.add(4, fuel / CAPACITY, CAPACITY, 90f32, true, false);
+        data.add(4, 0.9, CAPACITY, 90f32, true, false);
+        drive(&mut data, 5..7, 88f32);
+
+        let clean: Vec<i32> = data
+            .laps
+            .iter()
+            .filter(|l| l.clean)
+            .map(|l| l.lap)
+            .collect();
+        assert_eq!(clean, vec![2, 3, 5]);
+        assert!((data.per_lap().unwrap() - 2f32).abs() < 0.001);
+    }
+}
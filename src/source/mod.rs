@@ -0,0 +1,128 @@
+mod relay;
+#[cfg(windows)]
+mod shared;
+mod udp;
+
+pub use self::relay::*;
+#[cfg(windows)]
+pub use self::shared::*;
+pub use self::udp::*;
+
+use definitions::*;
+
+/// Anything that can give telemetry frames, read into `SharedMemory` whatever the game sent.
+pub trait TelemetrySource {
+    /// Newest complete frame, none when nothing changed since last call.
+    fn poll(&mut self) -> Option<SharedMemory>;
+
+    /// Game and protocol the frames come from.
+    fn describe(&self) -> String;
+}
+
+/// Layouts of `$pcars2$` that can be read into `SharedMemory`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryLayout {
+    /// Project CARS 2, SharedMemory9.h.
+    PCars2,
+}
+
+/// Every `mVersion` frames are read for, anything else is refused.
+/// New game versions go here with their own definition and offset tests,
+/// once their header is checked.
+pub const KNOWN_VERSIONS: [(u32, MemoryLayout); 1] =
+    [(SHARED_MEMORY_VERSION, MemoryLayout::PCars2)];
+
+impl MemoryLayout {
+    /// Layout game writes with that version, none for versions not known.
+    pub fn from_version(version: u32) -> Option<MemoryLayout> {
+        KNOWN_VERSIONS
+            .iter()
+            .find(|&&(known, _)| known == version)
+            .map(|&(_, layout)| layout)
+    }
+
+    /// For messages, "9" or "9, 10".
+    pub fn known_versions() -> String {
+        KNOWN_VERSIONS
+            .iter()
+            .map(|&(version, _)| version.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MemoryLayout::PCars2 => "Project CARS 2",
+        }
+    }
+}
+
+/// Source the platform has, shared memory of a running game on Windows.
+#[cfg(windows)]
+pub fn open_default() -> Result<Box<dyn TelemetrySource>, String> {
+    let source = SharedMemorySource::open()?;
+    Ok(Box::new(source))
+}
+
+/// No shared memory elsewhere, game has to broadcast to this machine.
+#[cfg(not(windows))]
+pub fn open_default() -> Result<Box<dyn TelemetrySource>, String> {
+    let source = UdpSource::bind(UDP_PORT)?;
+    Ok(Box::new(source))
+}
+
+/// Source picked by command line:
+/// `--udp [port]` listens for game broadcast,
+/// `--relay [port]` shows default source and streams it to viewers,
+/// `--viewer host[:port]` shows what a relay streams,
+/// anything else is default source.
+pub fn open_from_args<I>(mut args: I) -> Result<Box<dyn TelemetrySource>, String>
+where
+    I: Iterator<Item = String>,
+{
+    match args.next() {
+        Some(ref arg) if arg == "--udp" => {
+            let source = UdpSource::bind(port(args.next(), UDP_PORT)?)?;
+            Ok(Box::new(source))
+        }
+        Some(ref arg) if arg == "--relay" => {
+            let port = port(args.next(), RELAY_PORT)?;
+            let source = Relay::new(open_default()?, port)?;
+            Ok(Box::new(source))
+        }
+        Some(ref arg) if arg == "--viewer" => {
+            let address = args
+                .next()
+                .ok_or_else(|| "Relay address is missing".to_string())?;
+            let address = if address.contains(':') {
+                address
+            } else {
+                format!("{}:{}", address, RELAY_PORT)
+            };
+            let source = RelayViewer::connect(&address)?;
+            Ok(Box::new(source))
+        }
+        _ => open_default(),
+    }
+}
+
+fn port(arg: Option<String>, default: u16) -> Result<u16, String> {
+    match arg {
+        Some(port) => port.parse().map_err(|_| format!("Invalid port: {}", port)),
+        None => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_known_versions_have_layout() {
+        assert_eq!(MemoryLayout::from_version(9), Some(MemoryLayout::PCars2));
+        for version in [0, 8, 10, 12, 100, u32::max_value()].iter() {
+            assert_eq!(MemoryLayout::from_version(*version), None, "{}", version);
+        }
+        assert_eq!(MemoryLayout::known_versions(), "9");
+    }
+}
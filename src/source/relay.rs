@@ -0,0 +1,516 @@
+use super::*;
+use definitions::*;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+pub const RELAY_PORT: u16 = 5607;
+
+const MAGIC: &[u8; 4] = b"PGRL";
+//bump when frame encoding changes, viewers refuse anything else
+const PROTOCOL_VERSION: u8 = 1;
+const HEADER_SIZE: usize = 10;
+const FULL: u8 = 0;
+const DELTA: u8 = 1;
+//delta granularity, only blocks that changed since last frame are sent
+const BLOCK: usize = 32;
+//viewer that fell this far behind is dropped, it can connect again
+const PENDING_MAX: usize = 1 << 20;
+const RECONNECT: u64 = 2;
+
+fn frame_header(kind: u8, payload: usize) -> Vec<u8> {
+    let mut header = Vec::with_capacity(HEADER_SIZE + payload);
+    header.extend_from_slice(MAGIC);
+    header.push(PROTOCOL_VERSION);
+    header.push(kind);
+    for i in 0..4 {
+        header.push((payload >> (8 * i)) as u8);
+    }
+    header
+}
+
+fn full_frame(bytes: &[u8]) -> Vec<u8> {
+    let mut frame = frame_header(FULL, bytes.len());
+    frame.extend_from_slice(bytes);
+    frame
+}
+
+/// Bytes of changed blocks mask for a frame of that size.
+fn mask_size(frame: usize) -> usize {
+    let blocks = (frame + BLOCK - 1) / BLOCK;
+    (blocks + 7) / 8
+}
+
+/// Mask of changed blocks, then changed blocks in order.
+fn encode_delta(before: &[u8], after: &[u8]) -> Vec<u8> {
+    let mut mask = vec![0u8; mask_size(after.len())];
+    let mut changed = Vec::new();
+
+    for (ix, (a, b)) in before.chunks(BLOCK).zip(after.chunks(BLOCK)).enumerate() {
+        if a != b {
+            mask[ix / 8] |= 1 << (ix % 8);
+            changed.extend_from_slice(b);
+        }
+    }
+
+    let mut frame = frame_header(DELTA, mask.len() + changed.len());
+    frame.extend_from_slice(&mask);
+    frame.extend_from_slice(&changed);
+    frame
+}
+
+fn apply_delta(current: &mut [u8], payload: &[u8]) -> Result<(), String> {
+    let mask_size = mask_size(current.len());
+    if payload.len() < mask_size {
+        return Err("Relay frame is too short".to_string());
+    }
+
+    let (mask, mut changed) = payload.split_at(mask_size);
+    for (ix, block) in current.chunks_mut(BLOCK).enumerate() {
+        if mask[ix / 8] & (1 << (ix % 8)) != 0 {
+            if changed.len() < block.len() {
+                return Err("Relay frame is too short".to_string());
+            }
+            block.copy_from_slice(&changed[..block.len()]);
+            changed = &changed[block.len()..];
+        }
+    }
+    Ok(())
+}
+
+struct Viewer {
+    stream: TcpStream,
+    pending: Vec<u8>,
+    synced: bool,
+}
+
+impl Viewer {
+    /// False when viewer is gone or too slow to keep.
+    fn flush(&mut self) -> bool {
+        while !self.pending.is_empty() {
+            match self.stream.write(&self.pending) {
+                Ok(0) => return false,
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => return false,
+            }
+        }
+        self.pending.len() < PENDING_MAX
+    }
+}
+
+/// Passes frames of another source through, and streams them to every connected viewer.
+pub struct Relay {
+    inner: Box<dyn TelemetrySource>,
+    listener: TcpListener,
+    port: u16,
+    viewers: Vec<Viewer>,
+    last: Vec<u8>,
+}
+
+impl Relay {
+    pub fn new(inner: Box<dyn TelemetrySource>, port: u16) -> Result<Relay, String> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .map_err(|e| format!("Can't relay on TCP port {}: {}", port, e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Can't relay on TCP port {}: {}", port, e))?;
+
+        Ok(Relay {
+            inner,
+            listener,
+            port,
+            viewers: Vec::new(),
+            last: Vec::new(),
+        })
+    }
+
+    fn accept(&mut self) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                let _ = stream.set_nodelay(true);
+                self.viewers.push(Viewer {
+                    stream,
+                    pending: Vec::new(),
+                    synced: false,
+                });
+            }
+        }
+    }
+}
+
+impl TelemetrySource for Relay {
+    fn poll(&mut self) -> Option<SharedMemory> {
+        self.accept();
+
+        let mut frame = self.inner.poll();
+        if let Some(ref mut frame) = frame {
+            //padding goes out as is, so it must not differ between equal frames
+            frame.zero_padding();
+            let bytes = frame.as_bytes();
+            let delta = if self.last.len() == bytes.len() {
+                Some(encode_delta(&self.last, bytes))
+            } else {
+                None
+            };
+
+            for viewer in self.viewers.iter_mut() {
+                match delta {
+                    Some(ref delta) if viewer.synced => viewer.pending.extend_from_slice(delta),
+                    _ => {
+                        viewer.pending.extend_from_slice(&full_frame(bytes));
+                        viewer.synced = true;
+                    }
+                }
+            }
+
+            self.last.clear();
+            self.last.extend_from_slice(bytes);
+        }
+
+        let mut kept = Vec::with_capacity(self.viewers.len());
+        for mut viewer in self.viewers.drain(..) {
+            if viewer.flush() {
+                kept.push(viewer);
+            }
+        }
+        self.viewers = kept;
+
+        frame
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{}, relayed on port {} to {} viewers",
+            self.inner.describe(),
+            self.port,
+            self.viewers.len()
+        )
+    }
+}
+
+/// Frames streamed by a `Relay` on another machine.
+pub struct RelayViewer {
+    address: String,
+    stream: Option<TcpStream>,
+    last_attempt: Instant,
+    buffer: Vec<u8>,
+    current: Option<Vec<u8>>,
+    error: Option<String>,
+}
+
+impl RelayViewer {
+    pub fn connect(address: &str) -> Result<RelayViewer, String> {
+        let stream = RelayViewer::open(address)?;
+        Ok(RelayViewer {
+            address: address.to_string(),
+            stream: Some(stream),
+            last_attempt: Instant::now(),
+            buffer: Vec::new(),
+            current: None,
+            error: None,
+        })
+    }
+
+    fn open(address: &str) -> Result<TcpStream, String> {
+        let stream = TcpStream::connect(address)
+            .map_err(|e| format!("Can't connect to relay {}: {}", address, e))?;
+        stream
+            .set_nonblocking(true)
+            .map_err(|e| format!("Can't connect to relay {}: {}", address, e))?;
+        Ok(stream)
+    }
+
+    fn disconnect(&mut self, error: String) {
+        self.stream = None;
+        self.buffer.clear();
+        self.current = None;
+        self.error = Some(error);
+        self.last_attempt = Instant::now();
+    }
+
+    /// Reads whatever arrived, error when connection is lost.
+    fn receive(&mut self) -> Result<(), String> {
+        let stream = match self.stream {
+            Some(ref mut stream) => stream,
+            None => return Ok(()),
+        };
+
+        let mut chunk = [0u8; 16 * 1024];
+        loop {
+            match stream.read(&mut chunk) {
+                Ok(0) => return Err("Relay closed connection".to_string()),
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(format!("Relay connection lost: {}", e)),
+            }
+        }
+    }
+
+    /// Applies complete frames from buffer, true if there was any.
+    fn decode(&mut self) -> Result<bool, String> {
+        let mut fresh = false;
+
+        while self.buffer.len() >= HEADER_SIZE {
+            if &self.buffer[..4] != MAGIC {
+                return Err("Not a relay".to_string());
+            }
+            if self.buffer[4] != PROTOCOL_VERSION {
+                return Err(format!(
+                    "Relay speaks version {}, viewer {}",
+                    self.buffer[4], PROTOCOL_VERSION
+                ));
+            }
+
+            let kind = self.buffer[5];
+            let payload = (0..4).fold(0usize, |v, i| v | (self.buffer[6 + i] as usize) << (8 * i));
+
+            //size comes from the other end, checked before any of it is buffered
+            match kind {
+                FULL if payload != SHARED_MEMORY_SIZE => {
+                    return Err(format!(
+                        "Relay frames are {} bytes, viewer expects {}",
+                        payload, SHARED_MEMORY_SIZE
+                    ))
+                }
+                DELTA if payload > SHARED_MEMORY_SIZE + mask_size(SHARED_MEMORY_SIZE) => {
+                    return Err(format!("Relay delta of {} bytes is too long", payload))
+                }
+                FULL | DELTA => (),
+                _ => return Err(format!("Unknown relay frame {}", kind)),
+            }
+
+            if self.buffer.len() < HEADER_SIZE + payload {
+                break;
+            }
+
+            {
+                let data = &self.buffer[HEADER_SIZE..HEADER_SIZE + payload];
+                if kind == FULL {
+                    self.current = Some(data.to_vec());
+                } else {
+                    match self.current {
+                        Some(ref mut current) => apply_delta(current, data)?,
+                        None => return Err("Relay sent delta before full frame".to_string()),
+                    }
+                }
+            }
+
+            self.buffer.drain(..HEADER_SIZE + payload);
+            fresh = true;
+        }
+
+        Ok(fresh)
+    }
+}
+
+impl TelemetrySource for RelayViewer {
+    fn poll(&mut self) -> Option<SharedMemory> {
+        if self.stream.is_none() {
+            if self.last_attempt.elapsed() < Duration::from_secs(RECONNECT) {
+                return None;
+            }
+            self.last_attempt = Instant::now();
+            match RelayViewer::open(&self.address) {
+                Ok(stream) => {
+                    self.stream = Some(stream);
+                    self.error = None;
+                }
+                Err(e) => {
+                    self.error = Some(e);
+                    return None;
+                }
+            }
+        }
+
+        let result = self.receive().and_then(|_| self.decode());
+        match result {
+            Ok(true) => self
+                .current
+                .as_ref()
+                .and_then(|c| SharedMemory::from_bytes(c)),
+            Ok(false) => None,
+            Err(e) => {
+                self.disconnect(e);
+                None
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.error {
+            Some(ref error) => format!("relay {}, {}", self.address, error),
+            None => format!("relay {}", self.address),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    fn viewer() -> RelayViewer {
+        RelayViewer {
+            address: String::new(),
+            stream: None,
+            last_attempt: Instant::now(),
+            buffer: Vec::new(),
+            current: None,
+            error: None,
+        }
+    }
+
+    fn frame(speed: f32, rpm: f32) -> Vec<u8> {
+        let mut frame = SharedMemory::zeroed();
+        frame.mSpeed = speed;
+        frame.mRpm = rpm;
+        frame.mParticipantInfo.data[3].mCurrentLapDistance = speed * 10f32;
+        frame.as_bytes().to_vec()
+    }
+
+    #[test]
+    fn padding_is_zeroed() {
+        let mut frame = SharedMemory::zeroed();
+        unsafe {
+            ptr::write_bytes(
+                &mut frame as *mut SharedMemory as *mut u8,
+                0xaa,
+                SHARED_MEMORY_SIZE,
+            );
+        }
+        frame.zero_padding();
+
+        //3 gaps in the frame, one in each participant
+        let zeroes = frame.as_bytes().iter().filter(|b| **b == 0).count();
+        assert_eq!(zeroes, 3 * 3 + STORED_PARTICIPANTS_MAX * 3);
+    }
+
+    #[test]
+    fn delta_round_trip() {
+        let before = frame(10f32, 3000f32);
+        let after = frame(12f32, 3000f32);
+
+        let delta = encode_delta(&before, &after);
+        //two changed blocks out of 643
+        assert_eq!(delta.len(), HEADER_SIZE + 81 + 2 * BLOCK);
+
+        let mut current = before.clone();
+        apply_delta(&mut current, &delta[HEADER_SIZE..]).unwrap();
+        assert!(current == after);
+
+        let unchanged = encode_delta(&after, &after);
+        assert_eq!(unchanged.len(), HEADER_SIZE + 81);
+    }
+
+    #[test]
+    fn full_then_delta() {
+        let first = frame(10f32, 3000f32);
+        let second = frame(12f32, 3500f32);
+        let third = frame(15f32, 4000f32);
+
+        let mut viewer = viewer();
+        viewer.buffer.extend_from_slice(&full_frame(&first));
+        assert_eq!(viewer.decode(), Ok(true));
+        assert!(viewer.current.as_ref() == Some(&first));
+
+        viewer
+            .buffer
+            .extend_from_slice(&encode_delta(&first, &second));
+        viewer
+            .buffer
+            .extend_from_slice(&encode_delta(&second, &third));
+        assert_eq!(viewer.decode(), Ok(true));
+        assert!(viewer.current.as_ref() == Some(&third));
+        assert!(viewer.buffer.is_empty());
+
+        let decoded = SharedMemory::from_bytes(viewer.current.as_ref().unwrap()).unwrap();
+        assert_eq!((decoded.mSpeed, decoded.mRpm), (15f32, 4000f32));
+    }
+
+    #[test]
+    fn short_frame_waits_for_rest() {
+        let full = full_frame(&frame(10f32, 3000f32));
+        let (head, tail) = full.split_at(HEADER_SIZE + 100);
+
+        let mut viewer = viewer();
+        viewer.buffer.extend_from_slice(&head[..4]);
+        assert_eq!(viewer.decode(), Ok(false));
+        viewer.buffer.extend_from_slice(&head[4..]);
+        assert_eq!(viewer.decode(), Ok(false));
+        assert!(viewer.current.is_none());
+
+        viewer.buffer.extend_from_slice(tail);
+        assert_eq!(viewer.decode(), Ok(true));
+        assert!(viewer.current.is_some());
+    }
+
+    #[test]
+    fn delta_shorter_than_its_blocks_is_rejected() {
+        let first = frame(10f32, 3000f32);
+        let delta = encode_delta(&first, &frame(12f32, 3000f32));
+        let mut short = frame_header(DELTA, delta.len() - HEADER_SIZE - 1);
+        short.extend_from_slice(&delta[HEADER_SIZE..delta.len() - 1]);
+
+        let mut viewer = viewer();
+        viewer.buffer.extend_from_slice(&full_frame(&first));
+        viewer.buffer.extend_from_slice(&short);
+        assert!(viewer.decode().is_err());
+    }
+
+    #[test]
+    fn wrong_version_is_rejected() {
+        let mut full = full_frame(&frame(10f32, 3000f32));
+        full[4] = PROTOCOL_VERSION + 1;
+
+        let mut viewer = viewer();
+        viewer.buffer.extend_from_slice(&full);
+        assert!(viewer.decode().unwrap_err().contains("version"));
+    }
+
+    #[test]
+    fn delta_before_full_frame_is_rejected() {
+        let first = frame(10f32, 3000f32);
+        let mut viewer = viewer();
+        viewer
+            .buffer
+            .extend_from_slice(&encode_delta(&first, &frame(12f32, 3000f32)));
+        assert!(viewer.decode().is_err());
+        assert!(viewer.current.is_none());
+    }
+
+    #[test]
+    fn wrong_frame_size_is_rejected_from_header() {
+        for &size in [100, SHARED_MEMORY_SIZE + 1].iter() {
+            let mut viewer = viewer();
+            viewer.buffer.extend_from_slice(&frame_header(FULL, size));
+            assert!(viewer.decode().unwrap_err().contains("bytes"), "{}", size);
+        }
+    }
+
+    #[test]
+    fn oversized_delta_is_rejected_from_header() {
+        let mut viewer = viewer();
+        viewer
+            .buffer
+            .extend_from_slice(&full_frame(&frame(10f32, 3000f32)));
+        //largest length header can give
+        let mut header = frame_header(DELTA, 0);
+        header[6..].copy_from_slice(&[0xFF; 4]);
+        viewer.buffer.extend_from_slice(&header);
+        assert!(viewer.decode().unwrap_err().contains("too long"));
+        assert!(viewer.current.is_some());
+    }
+
+    #[test]
+    fn longest_delta_waits_for_the_rest() {
+        let mut viewer = viewer();
+        let longest = SHARED_MEMORY_SIZE + mask_size(SHARED_MEMORY_SIZE);
+        viewer
+            .buffer
+            .extend_from_slice(&frame_header(DELTA, longest));
+        assert_eq!(viewer.decode(), Ok(false));
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+use definitions::*;
+use std::ffi::OsStr;
+use std::iter::once;
+use std::mem;
+use std::os::windows::ffi::OsStrExt;
+use std::ptr;
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::handleapi::*;
+use winapi::um::memoryapi::*;
+use winapi::um::winnt::*;
+
+// pub const MAP_OBJECT_NAME: &str = "$pcars2$";
+// pub const MAP_OBJECT_NAME: [u16; 9] = [36, 112, 99, 97, 114, 115, 50, 36, 0];
+
+/// `$pcars2$` file mapping, written by Project CARS 2.
+pub struct SharedMemorySource {
+    file_handle: HANDLE,
+    data: *const SharedMemory,
+    layout: MemoryLayout,
+    last_sequence: Option<u32>,
+}
+
+impl SharedMemorySource {
+    pub fn open() -> Result<SharedMemorySource, String> {
+        let file_name: Vec<u16> = OsStr::new("$pcars2$")
+            .encode_wide()
+            .chain(once(0))
+            .collect();
+
+        let file_handle = unsafe { OpenFileMappingW(PAGE_READONLY, 0, file_name.as_ptr()) };
+
+        if file_handle.is_null() {
+            return Err("Game is not open!".to_string());
+        }
+
+        //newer layouts only add fields at the end, version 9 part is all that is mapped
+        let size_of = mem::size_of::<SharedMemory>() as usize;
+
+        let data = unsafe {
+            MapViewOfFile(file_handle, PAGE_READONLY, 0, 0, size_of) as *const SharedMemory
+        };
+
+        if data.is_null() {
+            let msg = unsafe {
+                let msg = format!(
+                    "Shared data is invalid, check versions.\nError code: [{:?}]",
+                    GetLastError()
+                );
+                CloseHandle(file_handle);
+                msg
+            };
+            return Err(msg);
+        }
+
+        let version = unsafe { ptr::read_volatile(&(*data).mVersion) };
+        let layout = match MemoryLayout::from_version(version) {
+            Some(layout) => layout,
+            None => {
+                unsafe {
+                    UnmapViewOfFile(data as *const _);
+                    CloseHandle(file_handle);
+                }
+                return Err(format!(
+                    "Data version mismatch, found: [{}], known: [{}]",
+                    version,
+                    MemoryLayout::known_versions()
+                ));
+            }
+        };
+
+        Ok(SharedMemorySource {
+            file_handle,
+            data,
+            layout,
+            last_sequence: None,
+        })
+    }
+}
+
+impl TelemetrySource for SharedMemorySource {
+    fn poll(&mut self) -> Option<SharedMemory> {
+        let update_index = unsafe { ptr::read_volatile(&(*self.data).mSequenceNumber) };
+
+        //odd while game is writing
+        if update_index % 2 != 0 || Some(update_index) == self.last_sequence {
+            return None;
+        }
+
+        //every field is a plain number, so reading while game writes can't make invalid values
+        let copy = unsafe { ptr::read_volatile(self.data) };
+
+        if copy.mSequenceNumber != update_index {
+            return None;
+        }
+
+        //game restarted with another version
+        self.layout = MemoryLayout::from_version(copy.mVersion)?;
+        self.last_sequence = Some(update_index);
+        Some(copy)
+    }
+
+    fn describe(&self) -> String {
+        format!("{}, shared memory", self.layout.name())
+    }
+}
+
+impl Drop for SharedMemorySource {
+    fn drop(&mut self) {
+        unsafe {
+            UnmapViewOfFile(self.data as *const _);
+            CloseHandle(self.file_handle);
+        }
+    }
+}
@@ -0,0 +1,781 @@
+use super::*;
+use definitions::*;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::mem;
+use std::net::UdpSocket;
+
+pub const UDP_PORT: u16 = 5606;
+
+//packet types, SMS_UDP_Definitions.hpp (patch 5, protocol version 2)
+const CAR_PHYSICS: u8 = 0;
+const RACE_DEFINITION: u8 = 1;
+const PARTICIPANTS: u8 = 2;
+const TIMINGS: u8 = 3;
+const GAME_STATE: u8 = 4;
+const TIME_STATS: u8 = 7;
+const PARTICIPANT_VEHICLE_NAMES: u8 = 8;
+
+//packed sizes, shorter packets are dropped
+const TELEMETRY_SIZE: usize = 559;
+const RACE_DEFINITION_SIZE: usize = 307;
+const PARTICIPANTS_SIZE: usize = 1136;
+const TIMINGS_SIZE: usize = 1063;
+const GAME_STATE_SIZE: usize = 22;
+const TIME_STATS_SIZE: usize = 1040;
+const VEHICLE_NAMES_SIZE: usize = 1132;
+const CLASS_NAMES_SIZE: usize = 1452;
+
+const PARTICIPANTS_PER_PACKET: usize = 16;
+const UDP_PARTICIPANTS_MAX: usize = 32;
+const VEHICLES_PER_PACKET: usize = 16;
+const CLASSES_PER_PACKET: usize = 60;
+const CLASS_NAME_LENGTH: usize = 20;
+
+/// Little endian reads at byte offsets, length is checked before any field is read.
+struct Packet<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Packet<'a> {
+    fn u8(&self, at: usize) -> u8 {
+        self.data[at]
+    }
+
+    fn i8(&self, at: usize) -> i8 {
+        self.data[at] as i8
+    }
+
+    fn u16(&self, at: usize) -> u16 {
+        u16::from(self.data[at]) | u16::from(self.data[at + 1]) << 8
+    }
+
+    fn i16(&self, at: usize) -> i16 {
+        self.u16(at) as i16
+    }
+
+    fn u32(&self, at: usize) -> u32 {
+        (0..4).fold(0u32, |v, i| v | u32::from(self.data[at + i]) << (8 * i))
+    }
+
+    fn f32(&self, at: usize) -> f32 {
+        f32::from_bits(self.u32(at))
+    }
+
+    fn vec3(&self, at: usize) -> Vec3<f32> {
+        Vec3 {
+            x: self.f32(at),
+            y: self.f32(at + 4),
+            z: self.f32(at + 8),
+        }
+    }
+
+    /// 0 to 255 as 0 to 1.
+    fn unit(&self, at: usize) -> f32 {
+        f32::from(self.u8(at)) / 255f32
+    }
+
+    /// -127 to 127 as -1 to 1.
+    fn signed_unit(&self, at: usize) -> f32 {
+        f32::from(self.i8(at)) / 127f32
+    }
+
+    fn tyres<F>(&self, at: usize, size: usize, read: F) -> TyresArray<f32>
+    where
+        F: Fn(&Packet, usize) -> f32,
+    {
+        let mut tyres = TyresArray {
+            data: [0f32; TYRE_MAX],
+        };
+        for (i, tyre) in tyres.data.iter_mut().enumerate() {
+            *tyre = read(self, at + i * size);
+        }
+        tyres
+    }
+
+    fn string(&self, at: usize, length: usize) -> PCString {
+        let mut string = PCString::empty();
+        let length = length.min(STRING_LENGTH_MAX);
+        string.data[..length].copy_from_slice(&self.data[at..at + length]);
+        string
+    }
+}
+
+/// Project CARS 2 UDP broadcast, put together into the same frames shared memory gives.
+///
+/// Only fields the protocol has are filled, everything else stays zero.
+pub struct UdpSource {
+    socket: UdpSocket,
+    port: u16,
+    frame: SharedMemory,
+    buffer: Vec<u8>,
+    //vehicle of each participant, names come in separate packets
+    car_index: [u16; STORED_PARTICIPANTS_MAX],
+    vehicles: HashMap<u16, (u32, PCString)>,
+    classes: HashMap<u32, PCString>,
+}
+
+impl UdpSource {
+    pub fn bind(port: u16) -> Result<UdpSource, String> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))
+            .map_err(|e| format!("Can't listen on UDP port {}: {}", port, e))?;
+        socket
+            .set_nonblocking(true)
+            .map_err(|e| format!("Can't listen on UDP port {}: {}", port, e))?;
+
+        let mut frame = SharedMemory::zeroed();
+        frame.mVersion = SHARED_MEMORY_VERSION;
+        frame.mViewedParticipantIndex = -1;
+        frame.mNumParticipants = -1;
+
+        Ok(UdpSource {
+            socket,
+            port,
+            frame,
+            buffer: vec![0u8; 2048],
+            car_index: [0u16; STORED_PARTICIPANTS_MAX],
+            vehicles: HashMap::new(),
+            classes: HashMap::new(),
+        })
+    }
+
+    /// Applies one datagram, true when it was car physics, which makes a new frame.
+    pub fn apply(&mut self, data: &[u8]) -> bool {
+        if data.len() < 12 {
+            return false;
+        }
+
+        let packet = Packet { data };
+        let length = data.len();
+        match packet.u8(10) {
+            CAR_PHYSICS if length >= TELEMETRY_SIZE => {
+                self.apply_telemetry(&packet);
+                self.update_viewed();
+                return true;
+            }
+            RACE_DEFINITION if length >= RACE_DEFINITION_SIZE => self.apply_race(&packet),
+            PARTICIPANTS if length >= PARTICIPANTS_SIZE => self.apply_participants(&packet),
+            TIMINGS if length >= TIMINGS_SIZE => self.apply_timings(&packet),
+            GAME_STATE if length >= GAME_STATE_SIZE => self.apply_game_state(&packet),
+            TIME_STATS if length >= TIME_STATS_SIZE => self.apply_time_stats(&packet),
+            PARTICIPANT_VEHICLE_NAMES if length >= CLASS_NAMES_SIZE => self.apply_classes(&packet),
+            PARTICIPANT_VEHICLE_NAMES if length >= VEHICLE_NAMES_SIZE => {
+                self.apply_vehicles(&packet)
+            }
+            _ => return false,
+        }
+
+        self.update_viewed();
+        false
+    }
+
+    fn apply_telemetry(&mut self, p: &Packet) {
+        let f = &mut self.frame;
+
+        f.mViewedParticipantIndex = i32::from(p.i8(12));
+        f.mUnfilteredThrottle = p.unit(13);
+        f.mUnfilteredBrake = p.unit(14);
+        f.mUnfilteredSteering = p.signed_unit(15);
+        f.mUnfilteredClutch = p.unit(16);
+        f.mCarFlags = CarFlags::new(u32::from(p.u8(17)));
+        f.mOilTempCelsius = f32::from(p.i16(18));
+        f.mOilPressureKPa = f32::from(p.u16(20));
+        f.mWaterTempCelsius = f32::from(p.i16(22));
+        f.mWaterPressureKPa = f32::from(p.u16(24));
+        f.mFuelPressureKPa = f32::from(p.u16(26));
+        f.mFuelCapacity = f32::from(p.u8(28));
+        f.mBrake = p.unit(29);
+        f.mThrottle = p.unit(30);
+        f.mClutch = p.unit(31);
+        f.mFuelLevel = p.f32(32);
+        f.mSpeed = p.f32(36);
+        f.mRpm = f32::from(p.u16(40));
+        f.mMaxRPM = f32::from(p.u16(42));
+        f.mSteering = p.signed_unit(44);
+
+        //gear in low nibble, 15 is reverse, number of gears in high one
+        let gears = p.u8(45);
+        f.mGear = match gears & 0x0F {
+            15 => -1,
+            gear => i32::from(gear),
+        };
+        f.mNumGears = i32::from(gears >> 4);
+        //shared memory has it in percent
+        f.mBoostAmount = p.unit(46) * 100f32;
+        f.mCrashState = u32::from(p.u8(47));
+        f.mOdometerKM = p.f32(48);
+
+        f.mOrientation = p.vec3(52);
+        f.mLocalVelocity = p.vec3(64);
+        f.mWorldVelocity = p.vec3(76);
+        f.mAngularVelocity = p.vec3(88);
+        f.mLocalAcceleration = p.vec3(100);
+        f.mWorldAcceleration = p.vec3(112);
+        f.mExtentsCentre = p.vec3(124);
+
+        for i in 0..TYRE_MAX {
+            f.mTyreFlags.data[i] = u32::from(p.u8(136 + i));
+            f.mTerrain.data[i] = u32::from(p.u8(140 + i));
+        }
+        f.mTyreY = p.tyres(144, 4, |p, at| p.f32(at));
+        f.mTyreRPS = p.tyres(160, 4, |p, at| p.f32(at));
+        f.mTyreTemp = p.tyres(176, 1, |p, at| f32::from(p.u8(at)));
+        f.mTyreHeightAboveGround = p.tyres(180, 4, |p, at| p.f32(at));
+        f.mTyreWear = p.tyres(196, 1, |p, at| p.unit(at));
+        f.mBrakeDamage = p.tyres(200, 1, |p, at| p.unit(at));
+        f.mSuspensionDamage = p.tyres(204, 1, |p, at| p.unit(at));
+        f.mBrakeTempCelsius = p.tyres(208, 2, |p, at| f32::from(p.i16(at)));
+        f.mTyreTreadTemp = p.tyres(216, 2, |p, at| f32::from(p.u16(at)));
+        f.mTyreLayerTemp = p.tyres(224, 2, |p, at| f32::from(p.u16(at)));
+        f.mTyreCarcassTemp = p.tyres(232, 2, |p, at| f32::from(p.u16(at)));
+        f.mTyreRimTemp = p.tyres(240, 2, |p, at| f32::from(p.u16(at)));
+        f.mTyreInternalAirTemp = p.tyres(248, 2, |p, at| f32::from(p.u16(at)));
+        f.mWheelLocalPositionY = p.tyres(280, 4, |p, at| p.f32(at));
+        f.mSuspensionTravel = p.tyres(312, 4, |p, at| p.f32(at));
+        f.mSuspensionVelocity = p.tyres(328, 4, |p, at| p.f32(at));
+        f.mAirPressure = p.tyres(352, 2, |p, at| f32::from(p.u16(at)));
+
+        f.mEngineSpeed = p.f32(360);
+        f.mEngineTorque = p.f32(364);
+        f.mWings = [p.unit(368), p.unit(369)];
+        f.mHandBrake = p.unit(370);
+        f.mAeroDamage = p.unit(371);
+        f.mEngineDamage = p.unit(372);
+
+        for (i, compound) in f.mTyreCompound.data.iter_mut().enumerate() {
+            let at = 378 + i * TYRE_COMPOUND_NAME_LENGTH_MAX;
+            compound
+                .data
+                .copy_from_slice(&p.data[at..at + TYRE_COMPOUND_NAME_LENGTH_MAX]);
+        }
+        f.mTurboBoostPressure = p.f32(538);
+        f.mBrakeBias = p.unit(554);
+
+        //frames are told apart by sequence, even means complete like in shared memory
+        f.mSequenceNumber = f.mSequenceNumber.wrapping_add(2);
+    }
+
+    fn apply_race(&mut self, p: &Packet) {
+        let f = &mut self.frame;
+
+        f.mWorldFastestLapTime = p.f32(12);
+        f.mPersonalFastestLapTime = p.f32(16);
+        f.mPersonalFastestSector1Time = p.f32(20);
+        f.mPersonalFastestSector2Time = p.f32(24);
+        f.mPersonalFastestSector3Time = p.f32(28);
+        f.mWorldFastestSector1Time = p.f32(32);
+        f.mWorldFastestSector2Time = p.f32(36);
+        f.mWorldFastestSector3Time = p.f32(40);
+        f.mTrackLength = p.f32(44);
+        f.mTrackLocation = p.string(48, STRING_LENGTH_MAX);
+        f.mTrackVariation = p.string(112, STRING_LENGTH_MAX);
+        f.mTranslatedTrackLocation = p.string(176, STRING_LENGTH_MAX);
+        f.mTranslatedTrackVariation = p.string(240, STRING_LENGTH_MAX);
+
+        //top bit marks timed sessions, laps are unknown then
+        let laps = p.u16(304);
+        f.mLapsInEvent = if laps & 0x8000 != 0 {
+            0
+        } else {
+            u32::from(laps)
+        };
+        f.mEnforcedPitStopLap = i32::from(p.i8(306));
+    }
+
+    fn apply_participants(&mut self, p: &Packet) {
+        //partial packets count from one, sixteen participants each
+        let first = (p.u8(8).max(1) as usize - 1) * PARTICIPANTS_PER_PACKET;
+
+        for i in 0..PARTICIPANTS_PER_PACKET {
+            let ix = first + i;
+            if ix >= UDP_PARTICIPANTS_MAX {
+                break;
+            }
+            self.frame.mParticipantInfo.data[ix].mName = p.string(16 + i * 64, 64);
+            self.frame.mNationalities.data[ix] = p.u32(1040 + i * 4);
+        }
+    }
+
+    fn apply_timings(&mut self, p: &Packet) {
+        let f = &mut self.frame;
+
+        f.mNumParticipants = i32::from(p.i8(12)).min(UDP_PARTICIPANTS_MAX as i32);
+        //seconds here, milliseconds in shared memory
+        f.mEventTimeRemaining = p.f32(17) * 1000f32;
+        f.mSplitTimeAhead = p.f32(21);
+        f.mSplitTimeBehind = p.f32(25);
+        f.mSplitTime = p.f32(29);
+
+        let viewed = f.viewed_participant();
+        for ix in 0..UDP_PARTICIPANTS_MAX {
+            let at = 33 + ix * 32;
+            let info = &mut f.mParticipantInfo.data[ix];
+
+            info.mWorldPosition = Vec3 {
+                x: f32::from(p.i16(at)),
+                y: f32::from(p.i16(at + 2)),
+                z: f32::from(p.i16(at + 4)),
+            };
+            info.mCurrentLapDistance = f32::from(p.u16(at + 12));
+
+            //top bit is active flag
+            let position = p.u8(at + 14);
+            info.mIsActive = position >> 7;
+            info.mRacePosition = u32::from(position & 0x7F);
+
+            //low bits are sector, rest is extra world position precision
+            let sector = i32::from(p.u8(at + 15) & 0x07);
+            info.mCurrentSector = sector;
+
+            let flag = p.u8(at + 16);
+            f.mHighestFlagColours.data[ix] = u32::from(flag & 0x0F);
+            f.mHighestFlagReasons.data[ix] = u32::from(flag >> 4);
+
+            let pit = p.u8(at + 17);
+            f.mPitModes.data[ix] = u32::from(pit & 0x0F);
+            f.mPitSchedules.data[ix] = u32::from(pit >> 4);
+
+            //top bit marks human drivers
+            self.car_index[ix] = p.u16(at + 18) & 0x7FFF;
+
+            //top bit is invalidated lap
+            let race_state = p.u8(at + 20);
+            f.mRaceStates.data[ix] = u32::from(race_state & 0x7F);
+            f.mLapsInvalidated.data[ix] = race_state >> 7;
+
+            let lap = p.u8(at + 21);
+            info.mCurrentLap = i32::from(lap);
+            info.mLapsCompleted = u32::from(lap.max(1) - 1);
+
+            //running time of current sector, stays in place once sector is done
+            let sector_time = p.f32(at + 26);
+            match sector {
+                0 => f.mCurrentSector1Times.data[ix] = sector_time,
+                1 => f.mCurrentSector2Times.data[ix] = sector_time,
+                2 => f.mCurrentSector3Times.data[ix] = sector_time,
+                _ => {}
+            }
+
+            if Some(ix) == viewed {
+                f.mCurrentTime = p.f32(at + 22);
+            }
+        }
+    }
+
+    fn apply_game_state(&mut self, p: &Packet) {
+        let f = &mut self.frame;
+
+        f.mBuildVersionNumber = u32::from(p.u16(12));
+        //game state in low nibble, session state in high one
+        let state = p.u8(14);
+        f.mGameState = u32::from(state & 0x0F);
+        f.mSessionState = u32::from(state >> 4);
+        f.mAmbientTemperature = f32::from(p.i8(15));
+        f.mTrackTemperature = f32::from(p.i8(16));
+        f.mRainDensity = p.unit(17);
+        f.mSnowDensity = p.unit(18);
+        f.mWindSpeed = f32::from(p.i8(19));
+        f.mWindDirectionX = p.signed_unit(20);
+        f.mWindDirectionY = p.signed_unit(21);
+    }
+
+    fn apply_time_stats(&mut self, p: &Packet) {
+        let f = &mut self.frame;
+
+        for ix in 0..UDP_PARTICIPANTS_MAX {
+            let at = 16 + ix * 32;
+            f.mFastestLapTimes.data[ix] = p.f32(at);
+            f.mLastLapTimes.data[ix] = p.f32(at + 4);
+            f.mFastestSector1Times.data[ix] = p.f32(at + 12);
+            f.mFastestSector2Times.data[ix] = p.f32(at + 16);
+            f.mFastestSector3Times.data[ix] = p.f32(at + 20);
+        }
+    }
+
+    fn apply_vehicles(&mut self, p: &Packet) {
+        for i in 0..VEHICLES_PER_PACKET {
+            let at = 12 + i * 70;
+            let name = p.string(at + 6, 64);
+            if !name.is_empty() {
+                self.vehicles.insert(p.u16(at), (p.u32(at + 2), name));
+            }
+        }
+    }
+
+    fn apply_classes(&mut self, p: &Packet) {
+        for i in 0..CLASSES_PER_PACKET {
+            let at = 12 + i * (4 + CLASS_NAME_LENGTH);
+            let name = p.string(at + 4, CLASS_NAME_LENGTH);
+            if !name.is_empty() {
+                self.classes.insert(p.u32(at), name);
+            }
+        }
+    }
+
+    /// Per participant values copied to the fields shared memory has for viewed car only.
+    fn update_viewed(&mut self) {
+        for ix in 0..UDP_PARTICIPANTS_MAX {
+            let (car, class) = match self.vehicles.get(&self.car_index[ix]) {
+                Some(&(class, ref car)) => (
+                    car.clone(),
+                    self.classes
+                        .get(&class)
+                        .cloned()
+                        .unwrap_or_else(PCString::empty),
+                ),
+                None => (PCString::empty(), PCString::empty()),
+            };
+            self.frame.mCarNames.data[ix] = car;
+            self.frame.mCarClassNames.data[ix] = class;
+        }
+
+        let f = &mut self.frame;
+        if let Some(ix) = f.viewed_participant() {
+            f.mCarName = f.mCarNames.data[ix].clone();
+            f.mCarClassName = f.mCarClassNames.data[ix].clone();
+            f.mLapInvalidated = f.mLapsInvalidated.data[ix];
+            f.mRaceState = f.mRaceStates.data[ix];
+            f.mPitMode = f.mPitModes.data[ix];
+            f.mPitSchedule = f.mPitSchedules.data[ix];
+            f.mHighestFlagColour = f.mHighestFlagColours.data[ix];
+            f.mHighestFlagReason = f.mHighestFlagReasons.data[ix];
+            f.mLastLapTime = f.mLastLapTimes.data[ix];
+            f.mBestLapTime = f.mFastestLapTimes.data[ix];
+        }
+    }
+}
+
+impl TelemetrySource for UdpSource {
+    fn poll(&mut self) -> Option<SharedMemory> {
+        let mut fresh = false;
+        let mut buffer = mem::replace(&mut self.buffer, Vec::new());
+
+        loop {
+            match self.socket.recv(&mut buffer) {
+                Ok(length) => fresh |= self.apply(&buffer[..length]),
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                //e.g. connection reset from an earlier send, nothing to do about it
+                Err(_) => break,
+            }
+        }
+        self.buffer = buffer;
+
+        if fresh {
+            Some(self.frame.clone())
+        } else {
+            None
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("Project CARS 2, UDP port {}", self.port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Datagram of given type, fields written at their offsets.
+    struct Datagram(Vec<u8>);
+
+    impl Datagram {
+        fn new(kind: u8, size: usize) -> Datagram {
+            let mut data = vec![0u8; size];
+            data[10] = kind;
+            Datagram(data)
+        }
+
+        fn u8(mut self, at: usize, value: u8) -> Self {
+            self.0[at] = value;
+            self
+        }
+
+        fn u16(mut self, at: usize, value: u16) -> Self {
+            self.0[at] = value as u8;
+            self.0[at + 1] = (value >> 8) as u8;
+            self
+        }
+
+        fn u32(mut self, at: usize, value: u32) -> Self {
+            for i in 0..4 {
+                self.0[at + i] = (value >> (8 * i)) as u8;
+            }
+            self
+        }
+
+        fn f32(self, at: usize, value: f32) -> Self {
+            self.u32(at, value.to_bits())
+        }
+
+        fn string(mut self, at: usize, value: &str) -> Self {
+            self.0[at..at + value.len()].copy_from_slice(value.as_bytes());
+            self
+        }
+    }
+
+    fn source() -> UdpSource {
+        UdpSource::bind(0).unwrap()
+    }
+
+    fn frame_bytes(source: &UdpSource) -> Vec<u8> {
+        let mut frame = source.frame.clone();
+        frame.zero_padding();
+        frame.as_bytes().to_vec()
+    }
+
+    #[test]
+    fn car_physics() {
+        let packet = Datagram::new(CAR_PHYSICS, TELEMETRY_SIZE)
+            .u8(12, 3)
+            .u8(13, 255)
+            .u8(15, 0x81)
+            .u8(17, 0x02)
+            .u16(18, -5i16 as u16)
+            .u16(20, 450)
+            .f32(32, 42.5)
+            .f32(36, 55.5)
+            .u16(40, 7200)
+            .u16(42, 8500)
+            .u8(44, 127)
+            .u8(45, 0x6F)
+            .u8(46, 255)
+            .f32(100, 1.5)
+            .f32(108, -9.8)
+            .u8(140 + 2, 7)
+            .f32(160 + 3 * 4, 120.25)
+            .u8(176 + 1, 90)
+            .u8(196, 51)
+            .u16(208 + 3 * 2, -20i16 as u16)
+            .u16(224 + 2, 95)
+            .f32(312 + 4, 0.05)
+            .u16(352 + 6, 180)
+            .f32(364, 420.0)
+            .u8(368, 51)
+            .u8(369, 255)
+            .u8(372, 255)
+            .string(378 + TYRE_COMPOUND_NAME_LENGTH_MAX, "Soft Slick")
+            .f32(538, 1.2)
+            .u8(554, 153);
+
+        let mut source = source();
+        let sequence = source.frame.mSequenceNumber;
+        assert!(source.apply(&packet.0));
+
+        let f = &source.frame;
+        assert_eq!(f.mViewedParticipantIndex, 3);
+        assert_eq!(f.mUnfilteredThrottle, 1f32);
+        assert_eq!(f.mUnfilteredSteering, -1f32);
+        assert!(f.mCarFlags.engine_active());
+        assert_eq!((f.mOilTempCelsius, f.mOilPressureKPa), (-5f32, 450f32));
+        assert_eq!((f.mFuelLevel, f.mSpeed), (42.5, 55.5));
+        assert_eq!((f.mRpm, f.mMaxRPM), (7200f32, 8500f32));
+        assert_eq!(f.mSteering, 1f32);
+        assert_eq!((f.mGear, f.mNumGears), (-1, 6));
+        assert_eq!(f.mBoostAmount, 100f32);
+        assert_eq!(
+            (f.mLocalAcceleration.x, f.mLocalAcceleration.z),
+            (1.5, -9.8)
+        );
+        assert_eq!(f.mTerrain.data[2], 7);
+        assert_eq!(f.mTyreRPS.data[3], 120.25);
+        assert_eq!(f.mTyreTemp.data[1], 90f32);
+        assert_eq!(f.mTyreWear.data[0], 0.2);
+        assert_eq!(f.mBrakeTempCelsius.data[3], -20f32);
+        assert_eq!(f.mTyreLayerTemp.data[1], 95f32);
+        assert_eq!(f.mSuspensionTravel.data[1], 0.05);
+        assert_eq!(f.mAirPressure.data[3], 180f32);
+        assert_eq!(f.mEngineTorque, 420f32);
+        assert_eq!(f.mWings, [0.2, 1f32]);
+        assert_eq!(f.mEngineDamage, 1f32);
+        assert_eq!(&f.mTyreCompound.data[1].data[..11], b"Soft Slick\0");
+        assert_eq!(f.mTurboBoostPressure, 1.2);
+        assert_eq!(f.mBrakeBias, 0.6);
+        assert_eq!(f.mSequenceNumber, sequence + 2);
+    }
+
+    #[test]
+    fn race_definition() {
+        let packet = Datagram::new(RACE_DEFINITION, RACE_DEFINITION_SIZE)
+            .f32(16, 101.5)
+            .f32(44, 7004f32)
+            .string(48, "Spa-Francorchamps")
+            .string(112, "Grand Prix")
+            .u16(304, 20)
+            .u8(306, 0xFF);
+
+        let mut source = source();
+        assert!(!source.apply(&packet.0));
+        let f = &source.frame;
+        assert_eq!(f.mPersonalFastestLapTime, 101.5);
+        assert_eq!(f.mTrackLength, 7004f32);
+        assert_eq!(f.mTrackLocation.to_string(), "Spa-Francorchamps");
+        assert_eq!(f.mTrackVariation.to_string(), "Grand Prix");
+        assert_eq!(f.mLapsInEvent, 20);
+        assert_eq!(f.mEnforcedPitStopLap, -1);
+
+        //timed session
+        assert!(!source.apply(&packet.u16(304, 0x8000 | 30).0));
+        assert_eq!(source.frame.mLapsInEvent, 0);
+    }
+
+    #[test]
+    fn participants_of_second_packet() {
+        let packet = Datagram::new(PARTICIPANTS, PARTICIPANTS_SIZE)
+            .u8(8, 2)
+            .string(16 + 64, "Second Driver")
+            .u32(1040 + 4, 44);
+
+        let mut source = source();
+        source.apply(&packet.0);
+        let f = &source.frame;
+        assert_eq!(
+            f.mParticipantInfo.data[17].mName.to_string(),
+            "Second Driver"
+        );
+        assert_eq!(f.mNationalities.data[17], 44);
+        assert!(f.mParticipantInfo.data[1].mName.is_empty());
+    }
+
+    #[test]
+    fn timings() {
+        let at = 33 + 32;
+        let packet = Datagram::new(TIMINGS, TIMINGS_SIZE)
+            .u8(12, 12)
+            .f32(17, 600f32)
+            .f32(21, 1.5)
+            .u16(at, -300i16 as u16)
+            .u16(at + 12, 1500)
+            .u8(at + 14, 0x83)
+            .u8(at + 15, 0x09)
+            .u8(at + 16, 0x21)
+            .u8(at + 17, 0x12)
+            .u16(at + 18, 0x8005)
+            .u8(at + 20, 0x82)
+            .u8(at + 21, 4)
+            .f32(at + 22, 95.25)
+            .f32(at + 26, 31.5);
+
+        let mut source = source();
+        source.frame.mViewedParticipantIndex = 1;
+        source.apply(&packet.0);
+
+        let f = &source.frame;
+        let info = &f.mParticipantInfo.data[1];
+        assert_eq!(f.mNumParticipants, 12);
+        assert_eq!(f.mEventTimeRemaining, 600_000f32);
+        assert_eq!(f.mSplitTimeAhead, 1.5);
+        assert_eq!(info.mWorldPosition.x, -300f32);
+        assert_eq!(info.mCurrentLapDistance, 1500f32);
+        assert_eq!((info.mIsActive, info.mRacePosition), (1, 3));
+        assert_eq!(info.mCurrentSector, 1);
+        assert_eq!(
+            (f.mHighestFlagColours.data[1], f.mHighestFlagReasons.data[1]),
+            (1, 2)
+        );
+        assert_eq!((f.mPitModes.data[1], f.mPitSchedules.data[1]), (2, 1));
+        assert_eq!(source.car_index[1], 5);
+        assert_eq!((f.mRaceStates.data[1], f.mLapsInvalidated.data[1]), (2, 1));
+        assert_eq!((info.mCurrentLap, info.mLapsCompleted), (4, 3));
+        assert_eq!(f.mCurrentSector2Times.data[1], 31.5);
+        assert_eq!(f.mCurrentTime, 95.25);
+
+        //viewed participant's values copied to single car fields
+        assert_eq!((f.mRaceState, f.mLapInvalidated, f.mPitMode), (2, 1, 2));
+    }
+
+    #[test]
+    fn game_state() {
+        let packet = Datagram::new(GAME_STATE, GAME_STATE_SIZE)
+            .u16(12, 1122)
+            .u8(14, 0x52)
+            .u8(15, -3i8 as u8)
+            .u8(16, 28)
+            .u8(17, 255);
+
+        let mut source = source();
+        source.apply(&packet.0);
+        let f = &source.frame;
+        assert_eq!(f.mBuildVersionNumber, 1122);
+        assert_eq!((f.mGameState, f.mSessionState), (2, 5));
+        assert_eq!((f.mAmbientTemperature, f.mTrackTemperature), (-3f32, 28f32));
+        assert_eq!(f.mRainDensity, 1f32);
+    }
+
+    #[test]
+    fn time_stats() {
+        let at = 16 + 2 * 32;
+        let packet = Datagram::new(TIME_STATS, TIME_STATS_SIZE)
+            .f32(at, 98.5)
+            .f32(at + 4, 99.75)
+            .f32(at + 20, 30.25);
+
+        let mut source = source();
+        source.apply(&packet.0);
+        let f = &source.frame;
+        assert_eq!(f.mFastestLapTimes.data[2], 98.5);
+        assert_eq!(f.mLastLapTimes.data[2], 99.75);
+        assert_eq!(f.mFastestSector3Times.data[2], 30.25);
+    }
+
+    #[test]
+    fn vehicle_and_class_names_reach_viewed_car() {
+        let vehicles = Datagram::new(PARTICIPANT_VEHICLE_NAMES, VEHICLE_NAMES_SIZE)
+            .u16(12 + 70, 5)
+            .u32(12 + 70 + 2, 7)
+            .string(12 + 70 + 6, "Formula Renault 3.5");
+        let classes = Datagram::new(PARTICIPANT_VEHICLE_NAMES, CLASS_NAMES_SIZE)
+            .u32(12 + 24, 7)
+            .string(12 + 24 + 4, "FR3.5");
+        let timings = Datagram::new(TIMINGS, TIMINGS_SIZE)
+            .u8(12, 2)
+            .u16(33 + 32 + 18, 5);
+        let telemetry = Datagram::new(CAR_PHYSICS, TELEMETRY_SIZE).u8(12, 1);
+
+        let mut source = source();
+        source.apply(&vehicles.0);
+        source.apply(&classes.0);
+        source.apply(&timings.0);
+        assert!(source.apply(&telemetry.0));
+
+        let f = &source.frame;
+        assert_eq!(f.mCarNames.data[1].to_string(), "Formula Renault 3.5");
+        assert_eq!(f.mCarName.to_string(), "Formula Renault 3.5");
+        assert_eq!(f.mCarClassName.to_string(), "FR3.5");
+        assert!(f.mCarNames.data[0].is_empty());
+    }
+
+    #[test]
+    fn short_packets_are_dropped() {
+        let sizes = [
+            (CAR_PHYSICS, TELEMETRY_SIZE),
+            (RACE_DEFINITION, RACE_DEFINITION_SIZE),
+            (PARTICIPANTS, PARTICIPANTS_SIZE),
+            (TIMINGS, TIMINGS_SIZE),
+            (GAME_STATE, GAME_STATE_SIZE),
+            (TIME_STATS, TIME_STATS_SIZE),
+            (PARTICIPANT_VEHICLE_NAMES, VEHICLE_NAMES_SIZE),
+        ];
+
+        let mut source = source();
+        let before = frame_bytes(&source);
+        for &(kind, size) in sizes.iter() {
+            let packet = Datagram(vec![0x11; size - 1]).u8(10, kind);
+            assert!(!source.apply(&packet.0), "type {}", kind);
+        }
+        assert!(!source.apply(&[0u8; 11]));
+        assert!(frame_bytes(&source) == before);
+        assert!(source.vehicles.is_empty());
+    }
+
+    #[test]
+    fn unknown_packet_types_are_dropped() {
+        let mut source = source();
+        let before = frame_bytes(&source);
+        for kind in [5u8, 6, 9, 200].iter() {
+            let packet = Datagram(vec![0x11; CLASS_NAMES_SIZE]).u8(10, *kind);
+            assert!(!source.apply(&packet.0), "type {}", kind);
+        }
+        assert!(frame_bytes(&source) == before);
+    }
+}
@@ -1,6 +1,6 @@
 use ggez::graphics::*;
 use ggez::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub struct NumericTextCache {
     pub numbers: HashMap<i32, graphics::Text>,
@@ -61,3 +61,182 @@ impl OptimizedText {
         Ok(())
     }
 }
+
+pub struct TextCache {
+    font: graphics::Font,
+    texts: HashMap<String, graphics::Text>,
+}
+
+impl TextCache {
+    pub fn new(font: graphics::Font) -> TextCache {
+        TextCache {
+            font,
+            texts: HashMap::new(),
+        }
+    }
+
+    pub fn get<'a>(
+        &'a mut self,
+        ctx: &mut Context,
+        content: &str,
+    ) -> GameResult<&'a graphics::Text> {
+        //labels with lap times and names keep coming, don't grow forever
+        if self.texts.len() > 2048 && !self.texts.contains_key(content) {
+            self.texts.clear();
+        }
+
+        if !self.texts.contains_key(content) {
+            let text = graphics::Text::new(ctx, content, &self.font)?;
+            self.texts.insert(content.to_string(), text);
+        }
+
+        Ok(&self.texts[content])
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, content: &str, dest: Point2) -> GameResult<f32> {
+        let text = self.get(ctx, content)?;
+        graphics::draw(ctx, text, dest, 0f32)?;
+        Ok(text.width() as f32)
+    }
+}
+
+//g per grip histogram bin, anything above the last bin is counted in it
+const GRIP_BIN: f32 = 0.02;
+const GRIP_BINS: usize = 250;
+//share of samples at or under peak grip, so kerb strikes and crashes don't set it
+const GRIP_PERCENTILE: f32 = 0.95;
+
+/// How often every g was seen in every speed bucket, peak is a high percentile of the bucket.
+#[derive(Clone, Debug)]
+pub struct PeakGrip {
+    step: i32,
+    buckets: BTreeMap<i32, Vec<u32>>,
+}
+
+impl PeakGrip {
+    /// Step is bucket width in km/h.
+    pub fn new(step: i32) -> PeakGrip {
+        PeakGrip {
+            step,
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Counts the sample, gives peak of the bucket it went into.
+    pub fn add(&mut self, speed: i32, g: f32) -> f32 {
+        let bin = ((g.max(0f32) / GRIP_BIN) as usize).min(GRIP_BINS - 1);
+        let counts = self
+            .buckets
+            .entry(speed - speed % self.step)
+            .or_insert_with(|| vec![0; GRIP_BINS]);
+        counts[bin] += 1;
+        percentile(counts)
+    }
+
+    /// Lowest speed of every bucket with its peak.
+    pub fn peaks(&self) -> Vec<(i32, f32)> {
+        self.buckets
+            .iter()
+            .map(|(bucket, counts)| (*bucket, percentile(counts)))
+            .collect()
+    }
+}
+
+fn percentile(counts: &[u32]) -> f32 {
+    let total: u32 = counts.iter().sum();
+    let target = (total as f32 * GRIP_PERCENTILE).ceil() as u32;
+    let mut seen = 0;
+    for (bin, count) in counts.iter().enumerate() {
+        seen += count;
+        if seen >= target {
+            return (bin as f32 + 0.5) * GRIP_BIN;
+        }
+    }
+    0f32
+}
+
+/// Running least squares fit of `y = slope * x + intercept`.
+/// Centred (Welford) updates in f64, so hours of samples don't lose precision.
+#[derive(Clone, Debug)]
+pub struct LinearFit {
+    n: u64,
+    mean_x: f64,
+    mean_y: f64,
+    //sums of squared and cross deviations from the means
+    cxx: f64,
+    cxy: f64,
+}
+
+impl LinearFit {
+    pub fn new() -> LinearFit {
+        LinearFit {
+            n: 0,
+            mean_x: 0f64,
+            mean_y: 0f64,
+            cxx: 0f64,
+            cxy: 0f64,
+        }
+    }
+
+    pub fn add(&mut self, x: f32, y: f32) {
+        let (x, y) = (x as f64, y as f64);
+        self.n += 1;
+        let dx = x - self.mean_x;
+        self.mean_x += dx / self.n as f64;
+        self.mean_y += (y - self.mean_y) / self.n as f64;
+        self.cxx += dx * (x - self.mean_x);
+        self.cxy += dx * (y - self.mean_y);
+    }
+
+    pub fn samples(&self) -> usize {
+        self.n as usize
+    }
+
+    pub fn slope(&self) -> Option<f32> {
+        if self.n < 2 || self.cxx * (self.n as f64) < 1e-6 {
+            None
+        } else {
+            Some((self.cxy / self.cxx) as f32)
+        }
+    }
+
+    pub fn intercept(&self) -> Option<f32> {
+        self.slope()
+            .map(|slope| (self.mean_y - slope as f64 * self.mean_x) as f32)
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Average {
+    sum: f32,
+    count: u32,
+}
+
+impl Average {
+    pub fn add(&mut self, value: f32) {
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Mean of whatever is there, for values already averaged, like one per lap.
+    pub fn mean(&self) -> Option<f32> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.sum / self.count as f32)
+        }
+    }
+
+    /// Nothing until there are enough samples to trust.
+    pub fn value(&self) -> Option<f32> {
+        if self.count < 10 {
+            None
+        } else {
+            Some(self.sum / self.count as f32)
+        }
+    }
+}